@@ -0,0 +1,237 @@
+use crate::decimal::Dec;
+use crate::error::BalanceChangeError;
+use crate::types::{DenomDefinition, MultiSend};
+use std::cmp::min;
+
+// Burn and commission that one input pays for a denom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputShare {
+    pub address: String,
+    pub burn: i128,
+    pub commission: i128,
+}
+
+// How the burn and commission of one denom are split between the inputs of a transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allocation {
+    pub denom: String,
+    // The sum of the inputs of the denom, without the issuer's.
+    pub non_issuer_input_sum: i128,
+    // The sum of the outputs of the denom, without the ones to the issuer.
+    pub non_issuer_output_sum: i128,
+    // The amount that burn and commission are charged on, min(non_issuer_input_sum, non_issuer_output_sum).
+    pub taxable_base: i128,
+    // One share per input that sends the denom, in the order of the inputs. The issuer's share is zero.
+    pub shares: Vec<InputShare>,
+    // The sum of the burn shares. Every share is rounded up, so this can be above burn_rate * total_burn.
+    pub total_burn_amount: i128,
+    // The sum of the commission shares, paid to the issuer.
+    pub total_commission_amount: i128,
+}
+
+impl Allocation {
+    // The share of the input of `address`, if it sends the denom.
+    pub fn share(&self, address: &str) -> Option<&InputShare> {
+        self.shares.iter().find(|share| share.address == address)
+    }
+}
+
+// Splits burn and commission of `definition.denom` between the inputs of `multi_send`, which must not
+// list an (address, denom) twice:
+//      - the issuer neither pays nor counts towards the sums
+//      - total_burn = min(non_issuer_input_sum, non_issuer_output_sum)
+//      - account_share = roundup(rate * total_burn * input_from_account / non_issuer_input_sum)
+// Each share is calculated from the exact fraction and rounded up once, the same for burn and commission.
+// The amounts must have passed `validate`: they are not checked again and must be positive.
+pub fn allocate(
+    definition: &DenomDefinition,
+    multi_send: &MultiSend,
+) -> Result<Allocation, BalanceChangeError> {
+    let denom = &definition.denom;
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("denom", denom = %denom).entered();
+    let overflow = || BalanceChangeError::Overflow {
+        denom: denom.clone(),
+    };
+    // (address, amount) of the inputs of the denom; the issuer's amount does not count.
+    let mut inputs: Vec<(&str, u128)> = Vec::new();
+    for input in multi_send.inputs.iter() {
+        for coin in input.coins.iter().filter(|coin| &coin.denom == denom) {
+            debug_assert!(coin.amount > 0, "allocate needs validated amounts");
+            let amount = if input.address == definition.issuer {
+                0
+            } else {
+                u128::try_from(coin.amount).map_err(|_| overflow())?
+            };
+            inputs.push((&input.address, amount));
+        }
+    }
+    let non_issuer_input_sum =
+        checked_sum(inputs.iter().map(|(_, amount)| *amount)).ok_or_else(overflow)?;
+    let mut outputs: Vec<u128> = Vec::new();
+    for output in multi_send.outputs.iter() {
+        for coin in output.coins.iter().filter(|coin| &coin.denom == denom) {
+            debug_assert!(coin.amount > 0, "allocate needs validated amounts");
+            if output.address != definition.issuer {
+                outputs.push(u128::try_from(coin.amount).map_err(|_| overflow())?);
+            }
+        }
+    }
+    let non_issuer_output_sum = checked_sum(outputs.into_iter()).ok_or_else(overflow)?;
+    let total_burn = min(non_issuer_input_sum, non_issuer_output_sum);
+    debug!(
+        non_issuer_input_sum,
+        non_issuer_output_sum,
+        taxable_base = total_burn,
+        burn_rate = %definition.burn_rate,
+        commission_rate = %definition.commission_rate,
+        "allocating fees"
+    );
+
+    let to_amount = |sum: u128| i128::try_from(sum).map_err(|_| overflow());
+    let mut allocation = Allocation {
+        denom: denom.clone(),
+        non_issuer_input_sum: to_amount(non_issuer_input_sum)?,
+        non_issuer_output_sum: to_amount(non_issuer_output_sum)?,
+        taxable_base: to_amount(total_burn)?,
+        shares: Vec::new(),
+        total_burn_amount: 0,
+        total_commission_amount: 0,
+    };
+    for (address, amount) in inputs {
+        let burn = account_share(
+            definition.burn_rate,
+            total_burn,
+            amount,
+            non_issuer_input_sum,
+        )
+        .ok_or_else(overflow)?;
+        let commission = account_share(
+            definition.commission_rate,
+            total_burn,
+            amount,
+            non_issuer_input_sum,
+        )
+        .ok_or_else(overflow)?;
+        allocation.total_burn_amount = allocation
+            .total_burn_amount
+            .checked_add(burn)
+            .ok_or_else(overflow)?;
+        allocation.total_commission_amount = allocation
+            .total_commission_amount
+            .checked_add(commission)
+            .ok_or_else(overflow)?;
+        allocation.shares.push(InputShare {
+            address: address.to_string(),
+            burn,
+            commission,
+        });
+    }
+    Ok(allocation)
+}
+
+// roundup(rate * total * amount / sum), zero if nothing is sent by non-issuers.
+fn account_share(rate: Dec, total: u128, amount: u128, sum: u128) -> Option<i128> {
+    if sum == 0 {
+        return Some(0);
+    }
+    let share = rate.checked_share_ceil(total, amount, sum)?;
+    i128::try_from(share).ok()
+}
+
+fn checked_sum(mut amounts: impl Iterator<Item = u128>) -> Option<u128> {
+    amounts.try_fold(0u128, |sum, amount| sum.checked_add(amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Balance;
+
+    #[test]
+    fn shares_of_the_spec_example() {
+        // the example of the spec: 10% burn, inputs 60 and 90 plus 25 from the issuer, outputs 50 and 25
+        // plus 100 to the issuer. total_burn = min(150, 75) = 75.
+        let definition =
+            DenomDefinition::new("denom1", "issuer").with_burn_rate(Dec::with_prec(1, 1));
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 60))
+            .input(Balance::new("account2").with_coin("denom1", 90))
+            .input(Balance::new("issuer").with_coin("denom1", 25))
+            .output(Balance::new("account3").with_coin("denom1", 50))
+            .output(Balance::new("issuer").with_coin("denom1", 100))
+            .output(Balance::new("account4").with_coin("denom1", 25));
+
+        let allocation = allocate(&definition, &multi_send).unwrap();
+
+        // 7.5 * 60 / 150 = 3 and 7.5 * 90 / 150 = 4.5, rounded up to 5.
+        assert_eq!(allocation.share("account1").unwrap().burn, 3);
+        assert_eq!(allocation.share("account2").unwrap().burn, 5);
+        assert_eq!(allocation.share("issuer").unwrap().burn, 0);
+        assert_eq!(allocation.taxable_base, 75);
+        assert_eq!(allocation.total_burn_amount, 8);
+        assert_eq!(allocation.total_commission_amount, 0);
+    }
+
+    #[test]
+    fn commission_is_rounded_once() {
+        // 1% of 150 is 1.5. Rounding the total up to 2 before splitting it would charge
+        // roundup(2 * 50 / 150) + roundup(2 * 100 / 150) = 1 + 2.
+        let definition = DenomDefinition::new("denom1", "issuer_account_A")
+            .with_commission_rate(Dec::with_prec(1, 2));
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 50))
+            .input(Balance::new("account2").with_coin("denom1", 100))
+            .output(Balance::new("account3").with_coin("denom1", 150));
+
+        let allocation = allocate(&definition, &multi_send).unwrap();
+
+        assert_eq!(
+            allocation.shares,
+            vec![
+                // roundup(1.5 * 50 / 150) = roundup(0.5)
+                InputShare {
+                    address: "account1".to_string(),
+                    burn: 0,
+                    commission: 1,
+                },
+                // 1.5 * 100 / 150 = 1
+                InputShare {
+                    address: "account2".to_string(),
+                    burn: 0,
+                    commission: 1,
+                },
+            ]
+        );
+        assert_eq!(allocation.total_commission_amount, 2);
+    }
+
+    #[test]
+    fn only_issuer_inputs_pay_nothing() {
+        let definition = DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(5, 1))
+            .with_commission_rate(Dec::with_prec(5, 1));
+        let multi_send = MultiSend::new()
+            .input(Balance::new("issuer_account_A").with_coin("denom1", 100))
+            .output(Balance::new("account1").with_coin("denom1", 100));
+
+        let allocation = allocate(&definition, &multi_send).unwrap();
+
+        assert_eq!(allocation.total_burn_amount, 0);
+        assert_eq!(allocation.total_commission_amount, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "allocate needs validated amounts")]
+    fn negative_output_is_not_allocated() {
+        let definition =
+            DenomDefinition::new("denom1", "issuer").with_burn_rate(Dec::with_prec(1, 1));
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account2").with_coin("denom1", 200))
+            .output(Balance::new("account3").with_coin("denom1", -100));
+
+        let _ = allocate(&definition, &multi_send);
+    }
+}
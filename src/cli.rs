@@ -0,0 +1,278 @@
+use rust_task::{
+    calculate_balance_changes, Balance, BalanceChangeError, DenomDefinition, MultiSend,
+};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+
+pub const USAGE: &str = "\
+usage: rust-task simulate --balances <file> --definitions <file> --tx <file> [--format json|table]
+
+Calculates the balance changes of a MultiSend without applying them.
+  --balances     JSON list of the original balances:
+                 [{\"address\": \"account1\", \"coins\": [{\"denom\": \"denom1\", \"amount\": \"1000\"}]}]
+  --definitions  JSON list of denom definitions:
+                 [{\"denom\": \"denom1\", \"issuer\": .., \"burn_rate\": \"0.08\", \"commission_rate\": \"0.12\"}]
+  --tx           JSON MultiSend in the layout of cosmos bank.MsgMultiSend: {\"inputs\": [..], \"outputs\": [..]}
+  --format       output format, json (default) or table
+
+Exits with 1 if the transaction would be rejected and 2 on invalid arguments or input files.";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Json,
+    Table,
+}
+
+#[derive(Debug, PartialEq)]
+struct SimulateArgs {
+    balances: String,
+    definitions: String,
+    tx: String,
+    format: Format,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    // The command line could not be understood.
+    Usage(String),
+    // An input file could not be read or parsed.
+    Input { path: String, message: String },
+    // The transaction itself would be rejected.
+    Rejected(BalanceChangeError),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Rejected(_) => 1,
+            CliError::Usage(_) | CliError::Input { .. } => 2,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CliError::Input { path, message } => write!(f, "{}: {}", path, message),
+            CliError::Rejected(error) => write!(f, "transaction rejected: {}", error),
+        }
+    }
+}
+
+// Runs the command line `args` (without the program name) and returns what should be printed.
+pub fn run(args: &[String]) -> Result<String, CliError> {
+    match args.first().map(String::as_str) {
+        Some("simulate") => simulate(&parse_simulate_args(&args[1..])?),
+        Some("help") | Some("--help") | Some("-h") => Ok(USAGE.to_string()),
+        Some(command) => Err(CliError::Usage(format!("unknown command {:?}", command))),
+        None => Err(CliError::Usage("missing command".to_string())),
+    }
+}
+
+fn parse_simulate_args(args: &[String]) -> Result<SimulateArgs, CliError> {
+    let mut balances = None;
+    let mut definitions = None;
+    let mut tx = None;
+    let mut format = Format::Json;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| CliError::Usage(format!("missing value for {}", flag)))?;
+        match flag.as_str() {
+            "--balances" => balances = Some(value.clone()),
+            "--definitions" => definitions = Some(value.clone()),
+            "--tx" => tx = Some(value.clone()),
+            "--format" => {
+                format = match value.as_str() {
+                    "json" => Format::Json,
+                    "table" => Format::Table,
+                    _ => return Err(CliError::Usage(format!("unknown format {:?}", value))),
+                }
+            }
+            _ => return Err(CliError::Usage(format!("unknown option {:?}", flag))),
+        }
+    }
+    let required = |value: Option<String>, flag: &str| {
+        value.ok_or_else(|| CliError::Usage(format!("missing {}", flag)))
+    };
+    Ok(SimulateArgs {
+        balances: required(balances, "--balances")?,
+        definitions: required(definitions, "--definitions")?,
+        tx: required(tx, "--tx")?,
+        format,
+    })
+}
+
+fn simulate(args: &SimulateArgs) -> Result<String, CliError> {
+    let original_balances: Vec<Balance> = read_json(&args.balances)?;
+    let definitions: Vec<DenomDefinition> = read_json(&args.definitions)?;
+    let multi_send: MultiSend = read_json(&args.tx)?;
+    let changes = calculate_balance_changes(original_balances, definitions, multi_send)
+        .map_err(CliError::Rejected)?;
+    match args.format {
+        Format::Json => {
+            Ok(serde_json::to_string_pretty(&changes).expect("balances serialize to JSON"))
+        }
+        Format::Table => Ok(format_table(&changes)),
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &str) -> Result<T, CliError> {
+    let input_error = |message: String| CliError::Input {
+        path: path.to_string(),
+        message,
+    };
+    let contents = fs::read_to_string(path).map_err(|error| input_error(error.to_string()))?;
+    serde_json::from_str(&contents).map_err(|error| input_error(error.to_string()))
+}
+
+// One row per (address, denom) change, with aligned columns.
+fn format_table(changes: &[Balance]) -> String {
+    let mut rows: Vec<[String; 3]> = vec![[
+        "ADDRESS".to_string(),
+        "DENOM".to_string(),
+        "CHANGE".to_string(),
+    ]];
+    for balance in changes {
+        for coin in balance.coins.iter() {
+            rows.push([
+                balance.address.clone(),
+                coin.denom.clone(),
+                coin.amount.to_string(),
+            ]);
+        }
+    }
+    let address_width = rows.iter().map(|row| row[0].len()).max().unwrap_or(0);
+    let denom_width = rows.iter().map(|row| row[1].len()).max().unwrap_or(0);
+    let change_width = rows.iter().map(|row| row[2].len()).max().unwrap_or(0);
+    rows.iter()
+        .map(|[address, denom, change]| {
+            format!(
+                "{:<address_width$}  {:<denom_width$}  {:>change_width$}",
+                address, denom, change
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_simulate_arguments() {
+        let parsed = parse_simulate_args(&args(&[
+            "--tx",
+            "tx.json",
+            "--balances",
+            "balances.json",
+            "--definitions",
+            "defs.json",
+            "--format",
+            "table",
+        ]));
+
+        assert_eq!(
+            parsed.unwrap(),
+            SimulateArgs {
+                balances: "balances.json".to_string(),
+                definitions: "defs.json".to_string(),
+                tx: "tx.json".to_string(),
+                format: Format::Table,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_arguments() {
+        let missing_tx = run(&args(&[
+            "simulate",
+            "--balances",
+            "balances.json",
+            "--definitions",
+            "defs.json",
+        ]));
+        assert!(matches!(missing_tx, Err(CliError::Usage(message)) if message == "missing --tx"));
+
+        let missing_value = run(&args(&["simulate", "--balances"]));
+        assert!(matches!(missing_value, Err(CliError::Usage(_))));
+
+        let unknown_command = run(&args(&["broadcast"]));
+        assert_eq!(unknown_command.unwrap_err().exit_code(), 2);
+    }
+
+    #[test]
+    fn missing_file_is_an_input_error() {
+        let result = run(&args(&[
+            "simulate",
+            "--balances",
+            "does-not-exist.json",
+            "--definitions",
+            "defs.json",
+            "--tx",
+            "tx.json",
+        ]));
+
+        assert!(matches!(
+            result,
+            Err(CliError::Input { path, .. }) if path == "does-not-exist.json"
+        ));
+    }
+
+    #[test]
+    fn rejected_transaction_exits_with_1() {
+        let error = CliError::Rejected(BalanceChangeError::UnknownDenom {
+            denom: "denom1".to_string(),
+        });
+
+        assert_eq!(error.exit_code(), 1);
+        assert_eq!(
+            error.to_string(),
+            "transaction rejected: no definition for denom denom1"
+        );
+    }
+
+    #[test]
+    fn formats_changes_as_a_table() {
+        let changes = vec![
+            Balance::new("account_recipient").with_coin("denom1", 1000),
+            Balance::new("account1").with_coin("denom1", -1200),
+        ];
+
+        assert_eq!(
+            format_table(&changes),
+            "ADDRESS            DENOM   CHANGE\n\
+             account_recipient  denom1    1000\n\
+             account1           denom1   -1200"
+        );
+    }
+
+    #[test]
+    fn simulates_example_files() {
+        let data = concat!(env!("CARGO_MANIFEST_DIR"), "/data/example_1");
+        let output = run(&args(&[
+            "simulate",
+            "--balances",
+            &format!("{}/balances.json", data),
+            "--definitions",
+            &format!("{}/definitions.json", data),
+            "--tx",
+            &format!("{}/multisend.json", data),
+        ]))
+        .unwrap();
+
+        let changes: Vec<Balance> = serde_json::from_str(&output).unwrap();
+        let account1 = changes.iter().find(|b| b.address == "account1").unwrap();
+        assert_eq!(account1.coins[0].amount, -1200);
+        let account2 = changes.iter().find(|b| b.address == "account2").unwrap();
+        assert_eq!(account2.coins[0].amount, -2000);
+    }
+}
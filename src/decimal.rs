@@ -0,0 +1,292 @@
+use crate::math::{mul_div_ceil, mul_div_floor, U256};
+#[cfg(feature = "serde")]
+use serde::de::{self, Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Number of decimal places kept by `Dec`, the same as `sdk.Dec` in the cosmos sdk.
+const PRECISION: usize = 18;
+const ONE_ATOMICS: u128 = 1_000_000_000_000_000_000;
+
+// A non-negative fixed-point decimal with 18 decimal places. It is used for burn and commission rates
+// and for every intermediate value of the fee calculation, so results do not depend on float rounding.
+// The value is stored as an integer number of 10^-18 units ("atomics").
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dec(u128);
+
+impl Dec {
+    pub const ZERO: Dec = Dec(0);
+    pub const ONE: Dec = Dec(ONE_ATOMICS);
+
+    // `value * 10^-prec`, like `sdk.NewDecWithPrec`: `Dec::with_prec(8, 2)` is 0.08.
+    // Panics if `prec` is above 18 or the value does not fit.
+    pub fn with_prec(value: u128, prec: u32) -> Dec {
+        assert!(prec as usize <= PRECISION, "precision {} is above 18", prec);
+        let atomics = value
+            .checked_mul(10u128.pow(PRECISION as u32 - prec))
+            .expect("decimal out of range");
+        Dec(atomics)
+    }
+
+    pub fn from_atomics(atomics: u128) -> Dec {
+        Dec(atomics)
+    }
+
+    // The value in units of 10^-18.
+    pub fn atomics(self) -> u128 {
+        self.0
+    }
+
+    pub fn from_int(value: u128) -> Option<Dec> {
+        value.checked_mul(ONE_ATOMICS).map(Dec)
+    }
+
+    // Exact multiplication by an integer.
+    pub fn checked_mul_int(self, value: u128) -> Option<Dec> {
+        self.0.checked_mul(value).map(Dec)
+    }
+
+    // `value` times the decimal, rounded down. Only fails if the result does not fit, the product
+    // itself is calculated with 256 bits.
+    pub fn checked_mul_int_truncate(self, value: u128) -> Option<u128> {
+        mul_div_floor(self.0, value, ONE_ATOMICS)
+    }
+
+    // `value` times the decimal, rounded up.
+    pub fn checked_mul_int_ceil(self, value: u128) -> Option<u128> {
+        mul_div_ceil(self.0, value, ONE_ATOMICS)
+    }
+
+    // ceil(self * total * part / whole), the part of `self * total` that falls on `part` of `whole`,
+    // with a single rounding at the end. `None` if `whole` is zero or the result does not fit.
+    pub fn checked_share_ceil(self, total: u128, part: u128, whole: u128) -> Option<u128> {
+        // total * part = q * whole + r, so the share is (self * q + self * r / whole) / 10^18.
+        let (q, r) = U256::mul(total, part).checked_div_rem(whole)?;
+        let (r_quotient, r_remainder) = U256::mul(self.0, r).checked_div_rem(whole)?;
+        let numerator = U256::mul(self.0, q.to_u128()?).checked_add(r_quotient)?;
+        let (share, remainder) = numerator.checked_div_rem(ONE_ATOMICS)?;
+        let share = share.to_u128()?;
+        // a fraction left in either division means the exact value is not an integer.
+        if remainder == 0 && r_remainder == 0 {
+            Some(share)
+        } else {
+            share.checked_add(1)
+        }
+    }
+
+    // Division by an integer, rounding the last decimal place up. Rounding up here keeps a later
+    // `ceil_int` exact: ceil(ceil(x * 10^18) / 10^18) == ceil(x).
+    pub fn checked_quo_int_round_up(self, value: u128) -> Option<Dec> {
+        let quotient = self.0.checked_div(value)?;
+        if self.0.is_multiple_of(value) {
+            Some(Dec(quotient))
+        } else {
+            quotient.checked_add(1).map(Dec)
+        }
+    }
+
+    // Smallest integer greater than or equal to the decimal.
+    pub fn ceil_int(self) -> u128 {
+        let integer = self.0 / ONE_ATOMICS;
+        if self.0.is_multiple_of(ONE_ATOMICS) {
+            integer
+        } else {
+            integer + 1
+        }
+    }
+
+    // Integer part of the decimal.
+    pub fn truncate_int(self) -> u128 {
+        self.0 / ONE_ATOMICS
+    }
+}
+
+impl fmt::Display for Dec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{:0width$}",
+            self.0 / ONE_ATOMICS,
+            self.0 % ONE_ATOMICS,
+            width = PRECISION
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDecError {
+    input: String,
+}
+
+impl fmt::Display for ParseDecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid decimal {:?}: expected digits with at most {} decimal places",
+            self.input, PRECISION
+        )
+    }
+}
+
+impl Error for ParseDecError {}
+
+// Parses plain decimal strings such as "0.08", "1" or "0.150000000000000000".
+impl FromStr for Dec {
+    type Err = ParseDecError;
+
+    fn from_str(s: &str) -> Result<Dec, ParseDecError> {
+        let error = || ParseDecError {
+            input: s.to_string(),
+        };
+        let (integer, fraction) = match s.split_once('.') {
+            Some((integer, fraction)) if !fraction.is_empty() => (integer, fraction),
+            Some(_) => return Err(error()),
+            None => (s, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty()
+            || !is_digits(integer)
+            || !is_digits(fraction)
+            || fraction.len() > PRECISION
+        {
+            return Err(error());
+        }
+        let integer: u128 = integer.parse().map_err(|_| error())?;
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", fraction, width = PRECISION)
+                .parse()
+                .map_err(|_| error())?
+        };
+        integer
+            .checked_mul(ONE_ATOMICS)
+            .and_then(|atomics| atomics.checked_add(fraction))
+            .map(Dec)
+            .ok_or_else(error)
+    }
+}
+
+// Decimals are (de)serialized as strings, the same way the cosmos sdk encodes `sdk.Dec` in JSON.
+#[cfg(feature = "serde")]
+impl Serialize for Dec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Dec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Dec, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Dec {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays_with_18_decimal_places() {
+        assert_eq!(dec("0.08").0, 80_000_000_000_000_000);
+        assert_eq!(dec("1").0, ONE_ATOMICS);
+        assert_eq!(dec("0.000000000000000001").0, 1);
+        assert_eq!(dec("0.08").to_string(), "0.080000000000000000");
+        assert_eq!(dec("12.5").to_string(), "12.500000000000000000");
+    }
+
+    #[test]
+    fn rejects_malformed_decimals() {
+        for input in [
+            "",
+            ".5",
+            "1.",
+            "-0.1",
+            "0.1.2",
+            "abc",
+            "0.0000000000000000001",
+            "1e-2",
+        ] {
+            assert!(
+                input.parse::<Dec>().is_err(),
+                "{:?} should not parse",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn multiplication_has_no_float_error() {
+        // 0.07 * 100 is 7.000000000000001 with f64, which rounds up to 8.
+        assert_eq!(dec("0.07").checked_mul_int(100).unwrap().ceil_int(), 7);
+        // 0.29 * 100 is 28.999999999999996 with f64, which truncates to 28.
+        assert_eq!(dec("0.29").checked_mul_int(100).unwrap().truncate_int(), 29);
+        // 0.1 * 3 is 0.30000000000000004 with f64.
+        assert_eq!(dec("0.1").checked_mul_int(3).unwrap(), dec("0.3"));
+    }
+
+    #[test]
+    fn division_rounds_up_the_last_decimal_place() {
+        let third = Dec::ONE.checked_quo_int_round_up(3).unwrap();
+        assert_eq!(third.0, 333_333_333_333_333_334);
+        assert_eq!(dec("7.5").checked_quo_int_round_up(5).unwrap(), dec("1.5"));
+        assert_eq!(Dec::ONE.checked_quo_int_round_up(0), None);
+    }
+
+    #[test]
+    fn with_prec_matches_parsing() {
+        assert_eq!(Dec::with_prec(8, 2), dec("0.08"));
+        assert_eq!(Dec::with_prec(15, 2), dec("0.15"));
+        assert_eq!(Dec::with_prec(1, 0), Dec::ONE);
+        assert_eq!(Dec::with_prec(1, 18), Dec::from_atomics(1));
+    }
+
+    #[test]
+    fn ceil_and_truncate() {
+        assert_eq!(dec("4.5").ceil_int(), 5);
+        assert_eq!(dec("4.5").truncate_int(), 4);
+        assert_eq!(dec("3").ceil_int(), 3);
+        assert_eq!(Dec(0).ceil_int(), 0);
+        assert_eq!(Dec(1).ceil_int(), 1);
+    }
+
+    #[test]
+    fn products_beyond_u128_are_exact() {
+        let rate = Dec::with_prec(8, 2);
+        let amount = i128::MAX as u128;
+
+        assert_eq!(rate.checked_mul_int(amount), None);
+        assert_eq!(
+            rate.checked_mul_int_truncate(amount),
+            Some(amount / 100 * 8 + 2)
+        );
+        assert_eq!(
+            rate.checked_mul_int_ceil(amount),
+            Some(amount / 100 * 8 + 3)
+        );
+    }
+
+    #[test]
+    fn share_is_rounded_up_once() {
+        let rate = Dec::with_prec(1, 1);
+
+        // 0.1 * 75 * 60 / 150 = 3 and 0.1 * 75 * 90 / 150 = 4.5
+        assert_eq!(rate.checked_share_ceil(75, 60, 150), Some(3));
+        assert_eq!(rate.checked_share_ceil(75, 90, 150), Some(5));
+        // 0.1 * 10 * 1 / 3 = 0.333.. has a fraction only in the division by 3.
+        assert_eq!(rate.checked_share_ceil(10, 1, 3), Some(1));
+        assert_eq!(
+            Dec::ONE.checked_share_ceil(u128::MAX, u128::MAX, u128::MAX),
+            Some(u128::MAX)
+        );
+        assert_eq!(rate.checked_share_ceil(10, 1, 0), None);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::decimal::Dec;
+use crate::types::Feature;
+use std::error::Error;
+use std::fmt;
+
+// Reasons for rejecting a `MultiSend` in `calculate_balance_changes`. Every variant carries enough
+// context (address, denom and the amounts involved) for callers to act on it without parsing strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BalanceChangeError {
+    // The sender does not hold enough of `denom` to cover the input amount on top of burn and commission.
+    InsufficientFunds {
+        address: String,
+        denom: String,
+        required: i128,
+        available: i128,
+    },
+    // The sum of inputs and the sum of outputs of `denom` are not equal.
+    InputOutputMismatch {
+        denom: String,
+        inputs: i128,
+        outputs: i128,
+    },
+    // `denom` is used in the transaction but there is no `DenomDefinition` for it.
+    UnknownDenom {
+        denom: String,
+    },
+    // The transaction has no inputs.
+    NoInputs,
+    // The transaction has no outputs.
+    NoOutputs,
+    // An input or output of `address` does not list any coins.
+    NoCoins {
+        address: String,
+    },
+    // An address that is empty or not in a valid format.
+    InvalidAddress {
+        address: String,
+    },
+    // A denom that is empty or does not match the cosmos denom format.
+    InvalidDenom {
+        denom: String,
+    },
+    // A coin amount that can not be transferred, i.e. zero or negative.
+    InvalidAmount {
+        address: String,
+        denom: String,
+        amount: i128,
+    },
+    // `address` lists `denom` more than once in the inputs or in the outputs, and duplicates are rejected.
+    DuplicateEntry {
+        address: String,
+        denom: String,
+    },
+    // A burn_rate or commission_rate outside of [0, 1].
+    InvalidRate {
+        denom: String,
+        rate: Dec,
+    },
+    // An intermediate amount of `denom` does not fit into the integer type used for it.
+    Overflow {
+        denom: String,
+    },
+    // `address` would send more of `denom` than the part of its balance that is not frozen.
+    FrozenFunds {
+        address: String,
+        denom: String,
+        required: i128,
+        spendable: i128,
+    },
+    // `address` sends `denom` to an account other than the issuer while the issuer halted its transfers
+    // with a global freeze.
+    GloballyFrozen {
+        address: String,
+        denom: String,
+    },
+    // An output would bring the balance of `address` to `balance`, above its whitelisted `limit` of `denom`.
+    WhitelistedLimitExceeded {
+        address: String,
+        denom: String,
+        balance: i128,
+        limit: i128,
+    },
+    // The operation needs `feature`, which the definition of `denom` does not enable.
+    FeatureDisabled {
+        denom: String,
+        feature: Feature,
+    },
+    // `address` is not the issuer of `denom` and may not perform the operation.
+    Unauthorized {
+        address: String,
+        denom: String,
+    },
+    // `address` is the issuer of `denom`, whose own balance the operation can not target.
+    IssuerNotAllowed {
+        address: String,
+        denom: String,
+    },
+    // `amount` of `denom` should be unfrozen for `address`, which only has `frozen` frozen.
+    NotFrozen {
+        address: String,
+        denom: String,
+        amount: i128,
+        frozen: i128,
+    },
+    // A UTXO transaction spends an output that was already spent, or spends it twice.
+    DoubleSpend {
+        tx_id: u64,
+        index: u32,
+    },
+    // A UTXO transaction spends an output that never existed.
+    UnknownOutPoint {
+        tx_id: u64,
+        index: u32,
+    },
+    // A UTXO transaction spends an output of `owner` without an input of `owner`.
+    OutPointNotOwned {
+        tx_id: u64,
+        index: u32,
+        owner: String,
+    },
+}
+
+impl fmt::Display for BalanceChangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceChangeError::InsufficientFunds {
+                address,
+                denom,
+                required,
+                available,
+            } => write!(
+                f,
+                "insufficient funds: {} needs {}{} but has {}{}",
+                address, required, denom, available, denom
+            ),
+            BalanceChangeError::InputOutputMismatch {
+                denom,
+                inputs,
+                outputs,
+            } => write!(
+                f,
+                "inputs do not match outputs for {}: inputs {}, outputs {}",
+                denom, inputs, outputs
+            ),
+            BalanceChangeError::UnknownDenom { denom } => {
+                write!(f, "no definition for denom {}", denom)
+            }
+            BalanceChangeError::NoInputs => write!(f, "transaction has no inputs"),
+            BalanceChangeError::NoOutputs => write!(f, "transaction has no outputs"),
+            BalanceChangeError::NoCoins { address } => {
+                write!(f, "no coins listed for {}", address)
+            }
+            BalanceChangeError::InvalidAddress { address } => {
+                write!(f, "invalid address {:?}", address)
+            }
+            BalanceChangeError::InvalidDenom { denom } => write!(f, "invalid denom {:?}", denom),
+            BalanceChangeError::InvalidAmount {
+                address,
+                denom,
+                amount,
+            } => write!(f, "invalid amount {}{} for {}", amount, denom, address),
+            BalanceChangeError::DuplicateEntry { address, denom } => {
+                write!(f, "{} is listed more than once for {}", denom, address)
+            }
+            BalanceChangeError::InvalidRate { denom, rate } => {
+                write!(
+                    f,
+                    "invalid rate {} for {}: must be between 0 and 1",
+                    rate, denom
+                )
+            }
+            BalanceChangeError::Overflow { denom } => {
+                write!(f, "arithmetic overflow while calculating {}", denom)
+            }
+            BalanceChangeError::FrozenFunds {
+                address,
+                denom,
+                required,
+                spendable,
+            } => write!(
+                f,
+                "frozen funds: {} needs {}{} but only {}{} are not frozen",
+                address, required, denom, spendable, denom
+            ),
+            BalanceChangeError::GloballyFrozen { address, denom } => {
+                write!(f, "{} is frozen, {} can not send it", denom, address)
+            }
+            BalanceChangeError::WhitelistedLimitExceeded {
+                address,
+                denom,
+                balance,
+                limit,
+            } => write!(
+                f,
+                "whitelisted limit exceeded: {} would hold {}{} but may hold {}{}",
+                address, balance, denom, limit, denom
+            ),
+            BalanceChangeError::FeatureDisabled { denom, feature } => {
+                write!(f, "{:?} is not enabled for {}", feature, denom)
+            }
+            BalanceChangeError::Unauthorized { address, denom } => {
+                write!(f, "{} is not the issuer of {}", address, denom)
+            }
+            BalanceChangeError::IssuerNotAllowed { address, denom } => write!(
+                f,
+                "{} is the issuer of {} and can not be the target of the operation",
+                address, denom
+            ),
+            BalanceChangeError::NotFrozen {
+                address,
+                denom,
+                amount,
+                frozen,
+            } => write!(
+                f,
+                "can not unfreeze {}{} of {}, only {}{} are frozen",
+                amount, denom, address, frozen, denom
+            ),
+            BalanceChangeError::DoubleSpend { tx_id, index } => {
+                write!(f, "output {}:{} is already spent", tx_id, index)
+            }
+            BalanceChangeError::UnknownOutPoint { tx_id, index } => {
+                write!(f, "output {}:{} does not exist", tx_id, index)
+            }
+            BalanceChangeError::OutPointNotOwned {
+                tx_id,
+                index,
+                owner,
+            } => write!(
+                f,
+                "output {}:{} belongs to {}, which is not an input of the transaction",
+                tx_id, index, owner
+            ),
+        }
+    }
+}
+
+impl Error for BalanceChangeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_have_readable_messages() {
+        let error = BalanceChangeError::InsufficientFunds {
+            address: "account1".to_string(),
+            denom: "denom1".to_string(),
+            required: 1200,
+            available: 1000,
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "insufficient funds: account1 needs 1200denom1 but has 1000denom1"
+        );
+    }
+}
@@ -0,0 +1,1648 @@
+use crate::allocation::{allocate, Allocation};
+use crate::error::BalanceChangeError;
+#[cfg(debug_assertions)]
+use crate::invariants::check_invariants;
+use crate::report::{build_report, BalanceChangeReport};
+use crate::types::{Balance, Coin, DenomDefinition, Feature, MultiSend};
+use crate::validation::{find_definition, merge_duplicates, validate, DuplicatePolicy};
+use std::collections::{BTreeMap, HashMap};
+
+// Implement `calculate_balance_changes` with the following requirements.
+// - Output of the function is the balance changes that must be applied to different accounts
+//   (negative means deduction, positive means addition), or an error. the error indicates that the transaction must be rejected.
+// - If sum of inputs and outputs in multi_send_tx does not match the tx must be rejected(i.e return error).
+// - Apply burn_rate and commission_rate as described by their definition.
+// - If the sender does not have enough balances (in the original_balances) to cover the input amount on top of burn_rate and
+// commission_rate, the transaction must be rejected.
+// - burn_rate and commission_rate does not apply to the issuer. So to calculate the correct values you must do this for every denom:
+//      - sum all the inputs coming from accounts that are not an issuer (let's call it non_issuer_input_sum)
+//      - sum all the outputs going to accounts that are not an issuer (let's call it non_issuer_output_sum)
+//      - total burn amount is total_burn = min(non_issuer_input_sum, non_issuer_output_sum)
+//      - total_burn is distributed between all input accounts as: account_share = roundup(total_burn * input_from_account / non_issuer_input_sum)
+//      - total_burn_amount = sum (account_shares) // notice that in previous step we rounded up, so we need to recalculate the total again.
+//      - commission_rate is exactly the same, but we send the calculate value to issuer, and not burn.
+//      - Example:
+//          burn_rate: 10%
+//
+//          inputs:
+//          60, 90
+//          25 <-- issuer
+//
+//          outputs:
+//          50
+//          100 <-- issuer
+//          25
+//          In this case burn amount is: min(non_issuer_inputs, non_issuer_outputs) = min(75+75, 50+25) = 75
+//          Expected burn: 75 * 10% = 7.5
+//          And now we divide it proportionally between all input sender: first_sender_share  = 7.5 * 60 / 150  = 3
+//                                                                        second_sender_share = 7.5 * 90 / 150  = 4.5
+// - In README.md we have provided more examples to help you better understand the requirements.
+// - Write different unit tests to cover all the edge cases, we would like to see how you structure your tests.
+//   There are examples in README.md, you can convert them into tests, but you should add more cases.
+pub fn calculate_balance_changes(
+    original_balances: Vec<Balance>,
+    definitions: Vec<DenomDefinition>,
+    multi_send_tx: MultiSend,
+) -> Result<Vec<Balance>, BalanceChangeError> {
+    calculate_balance_changes_with(
+        original_balances,
+        definitions,
+        multi_send_tx,
+        &Options::default(),
+    )
+    .map(|changes| changes.balances)
+}
+
+// Settings of `calculate_balance_changes_with`; `Options::default()` is what `calculate_balance_changes` uses.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    pub duplicates: DuplicatePolicy,
+    pub unknown_denoms: UnknownDenomPolicy,
+    pub funds: FundsPolicy,
+    // Whether `BalanceChanges::report` is filled in.
+    pub report: bool,
+}
+
+// How denoms without a `DenomDefinition` are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownDenomPolicy {
+    // The transaction is rejected with `BalanceChangeError::UnknownDenom`.
+    #[default]
+    Reject,
+    // The denom is transferred without burn and commission, as if it had zero rates and no issuer.
+    FeeFree,
+}
+
+// What the balances before the transaction must cover. Both use the final, rounded up shares.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FundsPolicy {
+    // Every input plus its burn and commission share must be covered by the sender's balance before the
+    // transaction, whatever the sender receives in the same transaction.
+    #[default]
+    StrictGross,
+    // Only the balances after applying all changes must not be negative, so what a sender receives in the
+    // transaction can pay for what it sends.
+    Net,
+}
+
+// Result of `calculate_balance_changes_with`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BalanceChanges {
+    // The balance changes, in the same canonical form that `calculate_balance_changes` returns.
+    pub balances: Vec<Balance>,
+    // The policy that applied to denoms without a definition.
+    pub unknown_denoms: UnknownDenomPolicy,
+    // Denoms without a definition that were transferred fee free, sorted.
+    pub fee_free_denoms: Vec<String>,
+    // The burnt amount per denom, the sum of the rounded up shares of the inputs. Sorted by denom,
+    // denoms without burn are left out.
+    pub total_burn_amount: Vec<Coin>,
+    // The commission paid to the issuer per denom, in the same form as `total_burn_amount`.
+    pub total_commission_amount: Vec<Coin>,
+    // The breakdown of the changes, if `Options::report` is set.
+    pub report: Option<BalanceChangeReport>,
+}
+
+impl Options {
+    pub fn new() -> Options {
+        Options::default()
+    }
+
+    pub fn with_duplicates(mut self, duplicates: DuplicatePolicy) -> Options {
+        self.duplicates = duplicates;
+        self
+    }
+
+    pub fn with_unknown_denoms(mut self, unknown_denoms: UnknownDenomPolicy) -> Options {
+        self.unknown_denoms = unknown_denoms;
+        self
+    }
+
+    pub fn with_funds(mut self, funds: FundsPolicy) -> Options {
+        self.funds = funds;
+        self
+    }
+
+    pub fn with_report(mut self) -> Options {
+        self.report = true;
+        self
+    }
+}
+
+// `calculate_balance_changes` with explicit `options`. The transaction is checked by `validate` before any
+// fee is calculated.
+pub fn calculate_balance_changes_with(
+    original_balances: Vec<Balance>,
+    definitions: Vec<DenomDefinition>,
+    multi_send_tx: MultiSend,
+    options: &Options,
+) -> Result<BalanceChanges, BalanceChangeError> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!(
+        "multi_send",
+        inputs = multi_send_tx.inputs.len(),
+        outputs = multi_send_tx.outputs.len()
+    )
+    .entered();
+    validate(&multi_send_tx, &definitions)?;
+    let multi_send_tx = MultiSend {
+        inputs: merge_duplicates(multi_send_tx.inputs, options.duplicates)?,
+        outputs: merge_duplicates(multi_send_tx.outputs, options.duplicates)?,
+    };
+    let mut definitions = definitions;
+    let mut fee_free_denoms: Vec<String> = Vec::new();
+    if options.unknown_denoms == UnknownDenomPolicy::FeeFree {
+        for balance in multi_send_tx
+            .inputs
+            .iter()
+            .chain(multi_send_tx.outputs.iter())
+        {
+            for coin in balance.coins.iter() {
+                if !definitions.iter().any(|d| d.denom == coin.denom)
+                    && !fee_free_denoms.contains(&coin.denom)
+                {
+                    fee_free_denoms.push(coin.denom.clone());
+                }
+            }
+        }
+        fee_free_denoms.sort();
+        // No address matches the empty issuer, and with zero rates there is no commission to pay it.
+        definitions.extend(
+            fee_free_denoms
+                .iter()
+                .map(|denom| DenomDefinition::new(denom.clone(), "")),
+        );
+    }
+    // the report needs the transaction and definitions that `apply_fees` consumes.
+    let report_input = options
+        .report
+        .then(|| (multi_send_tx.clone(), definitions.clone()));
+    #[cfg(debug_assertions)]
+    let invariant_input = (multi_send_tx.clone(), definitions.clone());
+    let (balances, mut allocations) =
+        apply_fees(original_balances, definitions, multi_send_tx, options.funds)?;
+    let report = match report_input {
+        Some((multi_send_tx, definitions)) => {
+            Some(build_report(&multi_send_tx, &definitions, &allocations)?)
+        }
+        None => None,
+    };
+    allocations.sort_by(|a, b| a.denom.cmp(&b.denom));
+    let totals = |amount: fn(&Allocation) -> i128| -> Vec<Coin> {
+        allocations
+            .iter()
+            .filter(|allocation| amount(allocation) != 0)
+            .map(|allocation| Coin::new(allocation.denom.clone(), amount(allocation)))
+            .collect()
+    };
+    let changes = BalanceChanges {
+        balances,
+        unknown_denoms: options.unknown_denoms,
+        fee_free_denoms,
+        total_burn_amount: totals(|allocation| allocation.total_burn_amount),
+        total_commission_amount: totals(|allocation| allocation.total_commission_amount),
+        report,
+    };
+    // a violation is a bug in the calculation, not a reason to reject the transaction.
+    #[cfg(debug_assertions)]
+    if let Err(violation) =
+        check_invariants(&invariant_input.0, &invariant_input.1, &changes.balances)
+    {
+        panic!("invariant violated: {}", violation);
+    }
+    Ok(changes)
+}
+
+// The balance changes of a validated transaction without duplicates, and how the fees of every denom
+// were allocated.
+fn apply_fees(
+    original_balances: Vec<Balance>,
+    definitions: Vec<DenomDefinition>,
+    multi_send_tx: MultiSend,
+    funds: FundsPolicy,
+) -> Result<(Vec<Balance>, Vec<Allocation>), BalanceChangeError> {
+    let mut denoms_to_update: Vec<String> = Vec::new();
+    let mut result_balances: Vec<Balance> = Vec::new();
+    let mut input_sum: HashMap<String, i128> = HashMap::new();
+    let mut output_sum: HashMap<String, i128> = HashMap::new();
+
+    for input in multi_send_tx.inputs.iter() {
+        for coin in input.coins.iter() {
+            if !denoms_to_update.contains(&coin.denom) {
+                denoms_to_update.push(coin.denom.clone());
+            }
+            find_definition(&definitions, &coin.denom)?;
+            add_amount(&mut input_sum, &coin.denom, coin.amount)?;
+        }
+    }
+    for output in multi_send_tx.outputs.iter() {
+        result_balances.push(output.clone());
+        for coin in output.coins.iter() {
+            if !denoms_to_update.contains(&coin.denom) {
+                denoms_to_update.push(coin.denom.clone());
+            }
+            find_definition(&definitions, &coin.denom)?;
+            add_amount(&mut output_sum, &coin.denom, coin.amount)?;
+        }
+    }
+    // while a denom is globally frozen it only moves from or to its issuer.
+    for input in multi_send_tx.inputs.iter() {
+        for coin in input.coins.iter() {
+            let coin_definition = find_definition(&definitions, &coin.denom)?;
+            if coin_definition.is_globally_frozen()
+                && input.address != coin_definition.issuer
+                && multi_send_tx.outputs.iter().any(|output| {
+                    output.address != coin_definition.issuer
+                        && output.coins.iter().any(|c| c.denom == coin.denom)
+                })
+            {
+                return Err(BalanceChangeError::GloballyFrozen {
+                    address: input.address.clone(),
+                    denom: coin.denom.clone(),
+                });
+            }
+        }
+    }
+    let mut allocations: Vec<Allocation> = Vec::new();
+    for value in denoms_to_update.into_iter() {
+        if input_sum.get(&value) != output_sum.get(&value) {
+            debug!(denom = %value, "inputs do not match outputs");
+            return Err(BalanceChangeError::InputOutputMismatch {
+                inputs: input_sum.get(&value).copied().unwrap_or(0),
+                outputs: output_sum.get(&value).copied().unwrap_or(0),
+                denom: value,
+            });
+        }
+        allocations.push(allocate(
+            find_definition(&definitions, &value)?,
+            &multi_send_tx,
+        )?);
+    }
+    if funds == FundsPolicy::StrictGross {
+        check_gross_funds(
+            &original_balances,
+            &definitions,
+            &multi_send_tx,
+            &allocations,
+        )?;
+    }
+
+    for input in multi_send_tx.inputs.into_iter() {
+        // finsih output
+        let mut balance_new = Balance::new(input.address.clone());
+        let existing_balance = result_balances
+            .iter()
+            .position(|b| b.address == input.address);
+        if let Some(balance_index) = existing_balance {
+            balance_new = result_balances[balance_index].clone();
+            result_balances.remove(balance_index);
+        }
+        for coin in input.coins.into_iter() {
+            let coin_definition = find_definition(&definitions, &coin.denom)?;
+            let denom = coin.denom.clone();
+            let overflow = || BalanceChangeError::Overflow {
+                denom: denom.clone(),
+            };
+            // every input of a denom has a share in the allocation of the denom.
+            let share = allocations
+                .iter()
+                .find(|allocation| allocation.denom == denom)
+                .and_then(|allocation| allocation.share(&input.address))
+                .expect("allocation covers every input");
+            let burn_account_share = share.burn;
+            let commission_account_share = share.commission;
+
+            let mut updated_coin_details = Coin::new(denom.clone(), 0);
+            let existing_coin = balance_new.coins.iter().position(|c| c.denom == coin.denom);
+            if let Some(existing_coin_index) = existing_coin {
+                updated_coin_details = balance_new.coins[existing_coin_index].clone();
+            }
+            trace!(
+                address = %input.address,
+                denom = %denom,
+                amount = coin.amount,
+                burn = burn_account_share,
+                commission = commission_account_share,
+                "input share"
+            );
+            updated_coin_details.amount = coin
+                .amount
+                .checked_add(burn_account_share)
+                .and_then(|deduct| deduct.checked_add(commission_account_share))
+                .and_then(|deduct| updated_coin_details.amount.checked_sub(deduct))
+                .ok_or_else(overflow)?;
+
+            if let Some(existing_coin_index) = existing_coin {
+                balance_new.coins[existing_coin_index] = updated_coin_details;
+            } else {
+                balance_new.coins.push(updated_coin_details);
+            }
+
+            //check if there is an issuer for this denom in result_balances
+            //if issuer exist, update issuer balance, if issuer does not exist, push a new balance of issuer with updated balance
+            let has_issuer = result_balances
+                .iter_mut()
+                .find(|b| b.address == coin_definition.issuer);
+
+            if commission_account_share > 0 {
+                match has_issuer {
+                    Some(b) => {
+                        // the issuer may so far only hold changes of other denoms.
+                        match b
+                            .coins
+                            .iter_mut()
+                            .find(|c| c.denom == coin_definition.denom)
+                        {
+                            Some(c) => {
+                                c.amount = c
+                                    .amount
+                                    .checked_add(commission_account_share)
+                                    .ok_or_else(overflow)?
+                            }
+                            None => b.coins.push(Coin {
+                                denom: coin_definition.denom.clone(),
+                                amount: commission_account_share,
+                            }),
+                        }
+                    }
+                    None => {
+                        let mut new_issuer_balance = Balance::new(coin_definition.issuer.clone());
+                        let mut coin_in_balance = Coin::new(coin_definition.denom.clone(), 0);
+                        coin_in_balance.amount += commission_account_share;
+                        new_issuer_balance.coins.push(coin_in_balance);
+                        result_balances.push(new_issuer_balance);
+                    }
+                };
+            }
+        }
+        result_balances.push(balance_new);
+    }
+    let changes = canonicalize(result_balances)?;
+    if funds == FundsPolicy::Net {
+        check_net_funds(&original_balances, &definitions, &changes)?;
+    }
+    check_whitelisting(
+        &original_balances,
+        &definitions,
+        &multi_send_tx.outputs,
+        &changes,
+    )?;
+    Ok((changes, allocations))
+}
+
+// Every input must be covered by the balance before the transaction, on top of its burn and commission share.
+fn check_gross_funds(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    multi_send_tx: &MultiSend,
+    allocations: &[Allocation],
+) -> Result<(), BalanceChangeError> {
+    for input in multi_send_tx.inputs.iter() {
+        for coin in input.coins.iter() {
+            let share = allocations
+                .iter()
+                .find(|allocation| allocation.denom == coin.denom)
+                .and_then(|allocation| allocation.share(&input.address))
+                .expect("allocation covers every input");
+            let required = coin
+                .amount
+                .checked_add(share.burn)
+                .and_then(|required| required.checked_add(share.commission))
+                .ok_or_else(|| BalanceChangeError::Overflow {
+                    denom: coin.denom.clone(),
+                })?;
+            check_funds(
+                original_balances,
+                definitions,
+                &input.address,
+                &coin.denom,
+                required,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// No balance may become negative once all changes are applied, so outputs to a sender count towards its inputs.
+fn check_net_funds(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    changes: &[Balance],
+) -> Result<(), BalanceChangeError> {
+    for change in changes.iter() {
+        for coin in change.coins.iter().filter(|coin| coin.amount < 0) {
+            let required =
+                coin.amount
+                    .checked_neg()
+                    .ok_or_else(|| BalanceChangeError::Overflow {
+                        denom: coin.denom.clone(),
+                    })?;
+            check_funds(
+                original_balances,
+                definitions,
+                &change.address,
+                &coin.denom,
+                required,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// `required` must be covered by the balance of `address`, without the part of it that is frozen.
+pub(crate) fn check_funds(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    address: &str,
+    denom: &str,
+    required: i128,
+) -> Result<(), BalanceChangeError> {
+    let available = balance_of(original_balances, address, denom);
+    trace!(address = %address, denom = %denom, required, available, "funds check");
+    if required > available {
+        debug!(address = %address, denom = %denom, "insufficient funds");
+        return Err(BalanceChangeError::InsufficientFunds {
+            address: address.to_string(),
+            denom: denom.to_string(),
+            required,
+            available,
+        });
+    }
+    // only denoms with the freezing feature can be frozen, and never the issuer's own balance.
+    let definition = find_definition(definitions, denom)?;
+    let frozen = definition.frozen_amount(address);
+    if frozen > 0 && address != definition.issuer && definition.has_feature(Feature::Freezing) {
+        let spendable = available.saturating_sub(frozen).max(0);
+        if required > spendable {
+            debug!(address = %address, denom = %denom, frozen, "frozen funds");
+            return Err(BalanceChangeError::FrozenFunds {
+                address: address.to_string(),
+                denom: denom.to_string(),
+                required,
+                spendable,
+            });
+        }
+    }
+    Ok(())
+}
+
+// No output may push the balance of a recipient above its whitelisted limit of a denom with the
+// whitelisting feature. The issuer has no limit.
+pub(crate) fn check_whitelisting(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    outputs: &[Balance],
+    changes: &[Balance],
+) -> Result<(), BalanceChangeError> {
+    for change in changes.iter() {
+        if !outputs
+            .iter()
+            .any(|output| output.address == change.address)
+        {
+            continue;
+        }
+        for coin in change.coins.iter().filter(|coin| coin.amount > 0) {
+            let definition = find_definition(definitions, &coin.denom)?;
+            if !definition.has_feature(Feature::Whitelisting) || change.address == definition.issuer
+            {
+                continue;
+            }
+            let limit = definition.whitelisted_limit(&change.address);
+            let balance = balance_of(original_balances, &change.address, &coin.denom)
+                .checked_add(coin.amount)
+                .ok_or_else(|| BalanceChangeError::Overflow {
+                    denom: coin.denom.clone(),
+                })?;
+            if balance > limit {
+                debug!(address = %change.address, denom = %coin.denom, balance, limit, "whitelisted limit exceeded");
+                return Err(BalanceChangeError::WhitelistedLimitExceeded {
+                    address: change.address.clone(),
+                    denom: coin.denom.clone(),
+                    balance,
+                    limit,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+// The amount of `denom` that `address` holds in `balances`.
+pub(crate) fn balance_of(balances: &[Balance], address: &str, denom: &str) -> i128 {
+    balances
+        .iter()
+        .filter(|balance| balance.address == address)
+        .flat_map(|balance| balance.coins.iter())
+        .find(|coin| coin.denom == denom)
+        .map_or(0, |coin| coin.amount)
+}
+
+// Brings the changes into their canonical form, independent of the order of inputs and outputs:
+// one entry per address, addresses sorted, coins sorted by denom and zero changes dropped.
+pub(crate) fn canonicalize(changes: Vec<Balance>) -> Result<Vec<Balance>, BalanceChangeError> {
+    let mut merged: BTreeMap<String, BTreeMap<String, i128>> = BTreeMap::new();
+    for balance in changes {
+        let coins = merged.entry(balance.address).or_default();
+        for coin in balance.coins {
+            let amount = coins.entry(coin.denom.clone()).or_insert(0);
+            *amount = amount
+                .checked_add(coin.amount)
+                .ok_or(BalanceChangeError::Overflow { denom: coin.denom })?;
+        }
+    }
+    Ok(merged
+        .into_iter()
+        .map(|(address, coins)| Balance {
+            address,
+            coins: coins
+                .into_iter()
+                .filter(|(_, amount)| *amount != 0)
+                .map(|(denom, amount)| Coin { denom, amount })
+                .collect(),
+        })
+        .filter(|balance| !balance.coins.is_empty())
+        .collect())
+}
+
+// Adds `amount` to the running sum of `denom`, creating the entry if needed.
+pub(crate) fn add_amount(
+    sums: &mut HashMap<String, i128>,
+    denom: &str,
+    amount: i128,
+) -> Result<(), BalanceChangeError> {
+    let sum = sums.entry(denom.to_string()).or_insert(0);
+    *sum = sum
+        .checked_add(amount)
+        .ok_or_else(|| BalanceChangeError::Overflow {
+            denom: denom.to_string(),
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+    use crate::types::AccountAmount;
+
+    #[test]
+    fn example_1_no_issuer_on_sender_or_receiver() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 1_000_000),
+            Balance::new("account2").with_coin("denom2", 1_000_000),
+        ];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(8, 2))
+                .with_commission_rate(Dec::with_prec(12, 2)),
+            DenomDefinition::new("denom2", "issuer_account_B").with_burn_rate(Dec::ONE),
+        ];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1000))
+            .input(Balance::new("account2").with_coin("denom2", 1000))
+            .output(
+                Balance::new("account_recipient")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 1000),
+            );
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        let balance_changes = vec![
+            // (1000 sent, 80 burnt, 120 send to issuer as commission)
+            Balance::new("account1").with_coin("denom1", -1200),
+            // (1000 sent and 1000 burnt(burn_rate is 1))
+            Balance::new("account2").with_coin("denom2", -2000),
+            Balance::new("account_recipient")
+                .with_coin("denom1", 1000)
+                .with_coin("denom2", 1000),
+            Balance::new("issuer_account_A").with_coin("denom1", 120),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn example_2_issuer_exists_on_sender_and_receiver() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 1_000_000),
+            Balance::new("account2").with_coin("denom1", 1_000_000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 650))
+            .input(Balance::new("account2").with_coin("denom1", 350))
+            .output(Balance::new("account_recipient").with_coin("denom1", 500))
+            .output(Balance::new("issuer_account_A").with_coin("denom1", 500));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        let balance_changes = vec![
+            // 650 sent, 26 burnt (650 * 500 / 1000 * 0.08), 39 send to issuer as commission (650 * 500 / 1000 * 0.12)
+            Balance::new("account1").with_coin("denom1", -715),
+            // 350 sent, 14 burnt (350 * 500 / 1000 * 0.08), 21 send to issuer as commission (350 * 500 / 1000 * 0.12)
+            Balance::new("account2").with_coin("denom1", -385),
+            Balance::new("account_recipient").with_coin("denom1", 500),
+            // 500 + 60 (sum of commissions)
+            Balance::new("issuer_account_A").with_coin("denom1", 560),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn example_3_not_enough_balance() {
+        let original_balances = vec![Balance::new("account1")];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 350))
+            .output(Balance::new("account_recipient").with_coin("denom1", 350));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 350,
+                available: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn example_4_input_output_mismatch() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 350))
+            .output(Balance::new("account_recipient").with_coin("denom1", 450));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InputOutputMismatch {
+                denom: "denom1".to_string(),
+                inputs: 350,
+                outputs: 450,
+            })
+        );
+    }
+
+    #[test]
+    fn example_5_demonstrate_rounding_up() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 1000),
+            Balance::new("account2").with_coin("denom1", 1000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(1, 2))
+            .with_commission_rate(Dec::with_prec(1, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1))
+            .input(Balance::new("account2").with_coin("denom1", 1))
+            .output(Balance::new("account_recipient").with_coin("denom1", 2));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        let balance_changes = vec![
+            // 1 sent, 1 burnt , 1 send to issuer as commission (1 = roundup(2 * 0.01))
+            Balance::new("account1").with_coin("denom1", -3),
+            // 1 sent, 1 burnt , 1 send to issuer as commission
+            Balance::new("account2").with_coin("denom1", -3),
+            Balance::new("account_recipient").with_coin("denom1", 2),
+            Balance::new("issuer_account_A").with_coin("denom1", 2),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn example_6_sender_is_also_a_receiver() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 10_000),
+            Balance::new("account2").with_coin("denom1", 10_000),
+            Balance::new("account3").with_coin("denom1", 10_000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(5, 1))
+            .with_commission_rate(Dec::with_prec(5, 1))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1600))
+            .input(Balance::new("account2").with_coin("denom1", 1000))
+            .output(Balance::new("account3").with_coin("denom1", 2000))
+            .output(Balance::new("account2").with_coin("denom1", 600));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        let balance_changes = vec![
+            // 1600 sent, 800 burnt , 800 send to issuer as commission
+            Balance::new("account1").with_coin("denom1", -3200),
+            // 1000 sent, 500 burnt , 500 send to issuer as commission and 600 received from account1
+            Balance::new("account2").with_coin("denom1", -1400),
+            Balance::new("account3").with_coin("denom1", 2000),
+            Balance::new("issuer_account_A").with_coin("denom1", 1300),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn example_7_issuer_and_a_sender_both_as_receiver() {
+        let original_balances = vec![
+            Balance::new("account1")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+            Balance::new("account2")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+        ];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 1))
+                .with_commission_rate(Dec::with_prec(5, 2)),
+            DenomDefinition::new("denom2", "issuer_account_B")
+                .with_burn_rate(Dec::with_prec(15, 2))
+                .with_commission_rate(Dec::with_prec(1, 1)),
+        ];
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 1000),
+            )
+            .input(
+                Balance::new("account2")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 2000),
+            )
+            .output(
+                Balance::new("account_recipient")
+                    .with_coin("denom1", 2000)
+                    .with_coin("denom2", 2000),
+            )
+            .output(Balance::new("account_recipient_2").with_coin("denom2", 250))
+            .output(Balance::new("issuer_account_B").with_coin("denom2", 500))
+            .output(Balance::new("account2").with_coin("denom2", 250));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        // Resulting Output:
+        let balance_changes = vec![
+            Balance::new("account1")
+                // 1000 sent, 100 burnt , 50 send to issuer as commission
+                .with_coin("denom1", -1150)
+                // 1000 sent, 125 burnt , 84 send to issuer as commission
+                .with_coin("denom2", -1209),
+            Balance::new("account2")
+                // 1000 sent, 100 burnt , 50 send to issuer as commission
+                .with_coin("denom1", -1150)
+                // 2000 sent, 250 burnt, 167 send to issuer as commission and 250 received from account1
+                .with_coin("denom2", -2167),
+            Balance::new("account_recipient")
+                .with_coin("denom1", 2000)
+                .with_coin("denom2", 2000),
+            Balance::new("account_recipient_2").with_coin("denom2", 250),
+            Balance::new("issuer_account_A").with_coin("denom1", 100),
+            // 500 sent, 84 commission from account1 and 167 commission from account2
+            Balance::new("issuer_account_B").with_coin("denom2", 751),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn unknown_denom_is_rejected() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom2", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom2", 100))
+            .output(Balance::new("account_recipient").with_coin("denom2", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::UnknownDenom {
+                denom: "denom2".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn negative_amount_is_rejected() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", -100))
+            .output(Balance::new("account_recipient").with_coin("denom1", -100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InvalidAmount {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                amount: -100,
+            })
+        );
+    }
+
+    #[test]
+    fn rate_above_one_is_rejected() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(15, 1))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InvalidRate {
+                denom: "denom1".to_string(),
+                rate: Dec::with_prec(15, 1),
+            })
+        );
+    }
+
+    #[test]
+    fn input_sum_overflow_is_rejected() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", i128::MAX),
+            Balance::new("account2").with_coin("denom1", i128::MAX),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", i128::MAX))
+            .input(Balance::new("account2").with_coin("denom1", 1))
+            .output(Balance::new("account_recipient").with_coin("denom1", i128::MAX));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::Overflow {
+                denom: "denom1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rates_with_float_error_round_exactly() {
+        // With f64, 100 * 0.07 is 7.000000000000001 and would round up to 8.
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(7, 2))
+            .with_commission_rate(Dec::with_prec(7, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            // 100 sent, 7 burnt, 7 send to issuer as commission
+            Balance::new("account1").with_coin("denom1", -114),
+            Balance::new("account_recipient").with_coin("denom1", 100),
+            Balance::new("issuer_account_A").with_coin("denom1", 7),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn balance_check_uses_exact_rates() {
+        // With f64, 100 * 0.29 is 28.999999999999996 and would let a balance of 128 pass.
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 128)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(29, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 129,
+                available: 128,
+            })
+        );
+    }
+
+    #[test]
+    fn issuer_sending_own_denom_pays_no_fees() {
+        let original_balances = vec![
+            Balance::new("issuer_account_A").with_coin("denom1", 100),
+            Balance::new("account1").with_coin("denom1", 1_000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(1, 1))
+            .with_commission_rate(Dec::with_prec(1, 1))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("issuer_account_A").with_coin("denom1", 100))
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 200));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            // 100 sent, 10 burnt, 10 send to issuer as commission
+            Balance::new("account1").with_coin("denom1", -120),
+            Balance::new("account_recipient").with_coin("denom1", 200),
+            // 100 sent without fees, 10 commission from account1
+            Balance::new("issuer_account_A").with_coin("denom1", -90),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn issuer_receiving_own_denom_is_not_taxed() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 10_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(1, 1))
+            .with_commission_rate(Dec::with_prec(1, 1))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1000))
+            .output(Balance::new("account_recipient").with_coin("denom1", 400))
+            .output(Balance::new("issuer_account_A").with_coin("denom1", 600));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            // 1000 sent, 40 burnt (min(1000, 400) * 0.1), 40 commission
+            Balance::new("account1").with_coin("denom1", -1080),
+            Balance::new("account_recipient").with_coin("denom1", 400),
+            // 600 received, 40 commission
+            Balance::new("issuer_account_A").with_coin("denom1", 640),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn issuer_of_another_denom_pays_fees_when_sending() {
+        let original_balances = vec![Balance::new("issuer_account_B").with_coin("denom1", 1_000)];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 1))
+                .with_commission_rate(Dec::with_prec(1, 1)),
+            DenomDefinition::new("denom2", "issuer_account_B"),
+        ];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("issuer_account_B").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            Balance::new("account_recipient").with_coin("denom1", 100),
+            Balance::new("issuer_account_A").with_coin("denom1", 10),
+            // 100 sent, 10 burnt, 10 send to issuer_account_A as commission
+            Balance::new("issuer_account_B").with_coin("denom1", -120),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn issuer_of_another_denom_is_taxed_when_receiving() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 1))
+                .with_commission_rate(Dec::with_prec(1, 1)),
+            DenomDefinition::new("denom2", "issuer_account_B"),
+        ];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("issuer_account_B").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            // 100 sent, 10 burnt, 10 send to issuer_account_A as commission
+            Balance::new("account1").with_coin("denom1", -120),
+            Balance::new("issuer_account_A").with_coin("denom1", 10),
+            Balance::new("issuer_account_B").with_coin("denom1", 100),
+        ];
+
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn equivalent_transactions_give_identical_changes() {
+        let original_balances = vec![
+            Balance::new("account1")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+            Balance::new("account2").with_coin("denom1", 10_000),
+        ];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 1))
+                .with_commission_rate(Dec::with_prec(1, 1)),
+            DenomDefinition::new("denom2", "issuer_account_B")
+                .with_commission_rate(Dec::with_prec(1, 1)),
+        ];
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 100)
+                    .with_coin("denom2", 100),
+            )
+            .input(Balance::new("account2").with_coin("denom1", 300))
+            .output(Balance::new("account3").with_coin("denom1", 400))
+            .output(Balance::new("account2").with_coin("denom2", 100));
+        let reordered = MultiSend::new()
+            .input(Balance::new("account2").with_coin("denom1", 300))
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom2", 100)
+                    .with_coin("denom1", 100),
+            )
+            .output(Balance::new("account2").with_coin("denom2", 100))
+            .output(Balance::new("account3").with_coin("denom1", 400));
+
+        let result =
+            calculate_balance_changes(original_balances.clone(), definitions.clone(), multi_send);
+        let reordered_result = calculate_balance_changes(original_balances, definitions, reordered);
+
+        let balance_changes = vec![
+            Balance::new("account1")
+                .with_coin("denom1", -120)
+                .with_coin("denom2", -110),
+            Balance::new("account2")
+                .with_coin("denom1", -360)
+                .with_coin("denom2", 100),
+            Balance::new("account3").with_coin("denom1", 400),
+            Balance::new("issuer_account_A").with_coin("denom1", 40),
+            Balance::new("issuer_account_B").with_coin("denom2", 10),
+        ];
+        assert_eq!(result, Ok(balance_changes));
+        assert_eq!(result, reordered_result);
+    }
+
+    #[test]
+    fn zero_changes_are_dropped() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        // half of the input goes back to account1, there is no change for the issuer without commission.
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 200))
+            .output(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account3").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            Balance::new("account1").with_coin("denom1", -100),
+            Balance::new("account3").with_coin("denom1", 100),
+        ];
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn duplicate_inputs_cannot_overspend() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        // each input alone fits the balance of account1, together they do not.
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 600))
+            .input(Balance::new("account1").with_coin("denom1", 600))
+            .output(Balance::new("account_recipient").with_coin("denom1", 1200));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 1200,
+                available: 1_000,
+            })
+        );
+    }
+
+    #[test]
+    fn duplicate_denoms_are_merged() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(1, 1))
+            .with_commission_rate(Dec::with_prec(1, 1))];
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 300)
+                    .with_coin("denom1", 200),
+            )
+            .output(Balance::new("account_recipient").with_coin("denom1", 500));
+        let merged = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 500))
+            .output(Balance::new("account_recipient").with_coin("denom1", 500));
+
+        let result =
+            calculate_balance_changes(original_balances.clone(), definitions.clone(), multi_send);
+
+        assert_eq!(
+            result,
+            calculate_balance_changes(original_balances, definitions, merged)
+        );
+        assert_eq!(
+            result,
+            Ok(vec![
+                // 500 sent, 50 burnt, 50 commission
+                Balance::new("account1").with_coin("denom1", -600),
+                Balance::new("account_recipient").with_coin("denom1", 500),
+                Balance::new("issuer_account_A").with_coin("denom1", 50),
+            ])
+        );
+    }
+
+    #[test]
+    fn duplicates_can_be_rejected() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 50))
+            .output(Balance::new("account_recipient").with_coin("denom1", 50));
+        let options = Options::new().with_duplicates(DuplicatePolicy::Reject);
+
+        let result =
+            calculate_balance_changes_with(original_balances, definitions, multi_send, &options);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::DuplicateEntry {
+                address: "account_recipient".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_transaction_is_rejected_before_fees() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        // the zero output would otherwise pass, as inputs and outputs still match.
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 100))
+            .output(Balance::new("account2").with_coin("denom1", 0));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InvalidAmount {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                amount: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_denom_can_be_fee_free() {
+        let original_balances = vec![Balance::new("account1")
+            .with_coin("denom1", 1_000)
+            .with_coin("denom2", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(1, 1))
+            .with_commission_rate(Dec::with_prec(1, 1))];
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 100)
+                    .with_coin("denom2", 100),
+            )
+            .output(
+                Balance::new("account_recipient")
+                    .with_coin("denom1", 100)
+                    .with_coin("denom2", 100),
+            );
+        let options = Options::new().with_unknown_denoms(UnknownDenomPolicy::FeeFree);
+
+        let result =
+            calculate_balance_changes_with(original_balances, definitions, multi_send, &options);
+
+        let balance_changes = vec![
+            // denom2 has no definition and pays no fees
+            Balance::new("account1")
+                .with_coin("denom1", -120)
+                .with_coin("denom2", -100),
+            Balance::new("account_recipient")
+                .with_coin("denom1", 100)
+                .with_coin("denom2", 100),
+            Balance::new("issuer_account_A").with_coin("denom1", 10),
+        ];
+        assert_eq!(
+            result,
+            Ok(BalanceChanges {
+                balances: balance_changes,
+                unknown_denoms: UnknownDenomPolicy::FeeFree,
+                fee_free_denoms: vec!["denom2".to_string()],
+                total_burn_amount: vec![Coin::new("denom1", 10)],
+                total_commission_amount: vec![Coin::new("denom1", 10)],
+                report: None,
+            })
+        );
+    }
+
+    #[test]
+    fn fee_free_denom_still_needs_funds() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom2", 50)];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom2", 100))
+            .output(Balance::new("account_recipient").with_coin("denom2", 100));
+        let options = Options::new().with_unknown_denoms(UnknownDenomPolicy::FeeFree);
+
+        let result =
+            calculate_balance_changes_with(original_balances, Vec::new(), multi_send, &options);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom2".to_string(),
+                required: 100,
+                available: 50,
+            })
+        );
+    }
+
+    #[test]
+    fn policy_is_recorded_when_all_denoms_are_defined() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1_000)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 100));
+
+        let result = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.unknown_denoms, UnknownDenomPolicy::Reject);
+        assert!(result.fee_free_denoms.is_empty());
+    }
+
+    #[test]
+    fn large_amounts_are_exact() {
+        // 10^30 units, e.g. 10^12 tokens with 18 decimals, beyond what an f64 or a u128 product holds.
+        let unit: i128 = 1_000_000_000_000_000_000_000_000_000_000;
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 2 * unit),
+            Balance::new("account2").with_coin("denom1", 4 * unit),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", unit))
+            .input(Balance::new("account2").with_coin("denom1", 3 * unit))
+            .output(Balance::new("account_recipient").with_coin("denom1", 4 * unit));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            // 10^30 sent, 8 * 10^28 burnt, 12 * 10^28 commission
+            Balance::new("account1").with_coin("denom1", -12 * unit / 10),
+            Balance::new("account2").with_coin("denom1", -36 * unit / 10),
+            Balance::new("account_recipient").with_coin("denom1", 4 * unit),
+            Balance::new("issuer_account_A").with_coin("denom1", 48 * unit / 100),
+        ];
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn amounts_at_i128_max_are_transferred() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", i128::MAX)];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", i128::MAX))
+            .output(Balance::new("account_recipient").with_coin("denom1", i128::MAX));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            Balance::new("account1").with_coin("denom1", -i128::MAX),
+            Balance::new("account_recipient").with_coin("denom1", i128::MAX),
+        ];
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn fees_above_i128_max_are_an_overflow() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", i128::MAX)];
+        let definitions =
+            vec![DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", i128::MAX))
+            .output(Balance::new("account_recipient").with_coin("denom1", i128::MAX));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::Overflow {
+                denom: "denom1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn full_burn_of_half_i128_max_fits() {
+        let half = i128::MAX / 2;
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", i128::MAX)];
+        let definitions =
+            vec![DenomDefinition::new("denom1", "issuer_account_A").with_burn_rate(Dec::ONE)];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", half))
+            .output(Balance::new("account_recipient").with_coin("denom1", half));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            Balance::new("account1").with_coin("denom1", -2 * half),
+            Balance::new("account_recipient").with_coin("denom1", half),
+        ];
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn commission_is_not_rounded_twice() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 1_000),
+            Balance::new("account2").with_coin("denom1", 1_000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_commission_rate(Dec::with_prec(1, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 50))
+            .input(Balance::new("account2").with_coin("denom1", 100))
+            .output(Balance::new("account_recipient").with_coin("denom1", 150));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        let balance_changes = vec![
+            // roundup(1.5 * 50 / 150) = roundup(0.5)
+            Balance::new("account1").with_coin("denom1", -51),
+            // 1.5 * 100 / 150 = 1, rounding 1.5 up to 2 first would make it roundup(1.33)
+            Balance::new("account2").with_coin("denom1", -101),
+            Balance::new("account_recipient").with_coin("denom1", 150),
+            Balance::new("issuer_account_A").with_coin("denom1", 2),
+        ];
+        assert_eq!(result, Ok(balance_changes));
+    }
+
+    #[test]
+    fn result_has_total_burn_and_commission() {
+        let original_balances = vec![
+            Balance::new("account1")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+            Balance::new("account2")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+        ];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 1))
+                .with_commission_rate(Dec::with_prec(5, 2)),
+            DenomDefinition::new("denom2", "issuer_account_B")
+                .with_burn_rate(Dec::with_prec(15, 2))
+                .with_commission_rate(Dec::with_prec(1, 1)),
+        ];
+        // example 7
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 1000),
+            )
+            .input(
+                Balance::new("account2")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 2000),
+            )
+            .output(
+                Balance::new("account_recipient")
+                    .with_coin("denom1", 2000)
+                    .with_coin("denom2", 2000),
+            )
+            .output(Balance::new("account_recipient_2").with_coin("denom2", 250))
+            .output(Balance::new("issuer_account_B").with_coin("denom2", 500))
+            .output(Balance::new("account2").with_coin("denom2", 250));
+
+        let result = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::default(),
+        )
+        .unwrap();
+
+        // denom2: 125 + 250 burnt and 84 + 167 commission
+        assert_eq!(
+            result.total_burn_amount,
+            vec![Coin::new("denom1", 200), Coin::new("denom2", 375)]
+        );
+        assert_eq!(
+            result.total_commission_amount,
+            vec![Coin::new("denom1", 100), Coin::new("denom2", 251)]
+        );
+    }
+
+    #[test]
+    fn gross_funds_are_checked_against_rounded_shares() {
+        // the commission share of account1 is roundup(1.5 * 50 / 150) = 1, a truncated 1% of 50 is 0.
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 50),
+            Balance::new("account2").with_coin("denom1", 1000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_commission_rate(Dec::with_prec(1, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 50))
+            .input(Balance::new("account2").with_coin("denom1", 100))
+            .output(Balance::new("account3").with_coin("denom1", 150));
+
+        let result = calculate_balance_changes(original_balances, definitions, multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 51,
+                available: 50,
+            })
+        );
+    }
+
+    // example 6 with account2 holding only 1000: it sends 1000 plus 1000 of fees and receives 600.
+    fn sender_receiving_part_of_its_spend() -> (Vec<Balance>, Vec<DenomDefinition>, MultiSend) {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 10_000),
+            Balance::new("account2").with_coin("denom1", 1_400),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(5, 1))
+            .with_commission_rate(Dec::with_prec(5, 1))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1600))
+            .input(Balance::new("account2").with_coin("denom1", 1000))
+            .output(Balance::new("account3").with_coin("denom1", 2000))
+            .output(Balance::new("account2").with_coin("denom1", 600));
+        (original_balances, definitions, multi_send)
+    }
+
+    #[test]
+    fn strict_gross_ignores_what_the_sender_receives() {
+        let (original_balances, definitions, multi_send) = sender_receiving_part_of_its_spend();
+
+        let result = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::new().with_funds(FundsPolicy::StrictGross),
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                required: 2000,
+                available: 1_400,
+            })
+        );
+    }
+
+    #[test]
+    fn net_funds_allow_the_received_amount_to_pay() {
+        let (original_balances, definitions, multi_send) = sender_receiving_part_of_its_spend();
+
+        let changes = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::new().with_funds(FundsPolicy::Net),
+        )
+        .unwrap();
+
+        // the balance of account2 ends at exactly 0.
+        assert_eq!(
+            changes.balances[1],
+            Balance::new("account2").with_coin("denom1", -1400)
+        );
+    }
+
+    #[test]
+    fn net_funds_reject_a_negative_final_balance() {
+        let (mut original_balances, definitions, multi_send) = sender_receiving_part_of_its_spend();
+        original_balances[1] = Balance::new("account2").with_coin("denom1", 1_399);
+
+        let result = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::new().with_funds(FundsPolicy::Net),
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                required: 1_400,
+                available: 1_399,
+            })
+        );
+    }
+
+    #[test]
+    fn input_dipping_into_frozen_balance_is_rejected() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let mut definition = DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(1, 1))
+            .with_feature(Feature::Freezing);
+        definition.frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 400,
+        });
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 550))
+            .output(Balance::new("account2").with_coin("denom1", 550));
+
+        let result = calculate_balance_changes(original_balances, vec![definition], multi_send);
+
+        // 550 + 55 of burn, but only 1000 - 400 are spendable.
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::FrozenFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 605,
+                spendable: 600,
+            })
+        );
+    }
+
+    #[test]
+    fn frozen_amounts_need_the_freezing_feature() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let mut definition = DenomDefinition::new("denom1", "issuer_account_A");
+        definition.frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 400,
+        });
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1000))
+            .output(Balance::new("account2").with_coin("denom1", 1000));
+
+        let result = calculate_balance_changes(original_balances, vec![definition], multi_send);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn global_freeze_needs_the_freezing_feature() {
+        let original_balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let mut definition = DenomDefinition::new("denom1", "issuer_account_A");
+        definition.globally_frozen = true;
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1000))
+            .output(Balance::new("account2").with_coin("denom1", 1000));
+
+        let result = calculate_balance_changes(original_balances, vec![definition], multi_send);
+
+        assert!(result.is_ok());
+    }
+}
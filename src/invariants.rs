@@ -0,0 +1,329 @@
+use crate::types::{Balance, DenomDefinition, MultiSend};
+use std::cmp::min;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+
+// Consistency checks of the balance changes that `calculate_balance_changes` returns, against the
+// transaction and definitions they were calculated from. The expected burn and commission are calculated
+// here from the transaction alone, so a result can not pass with wrong but self-consistent totals.
+// `calculate_balance_changes_with` runs the checks in debug builds and panics on a violation, tests can
+// call `check_invariants` directly.
+
+// An invariant that does not hold for a result, with the amounts that disagree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    // The changes of `denom` do not sum up to minus the expected burn: nothing but the burn may leave
+    // the accounts.
+    SupplyNotConserved {
+        denom: String,
+        delta_sum: i128,
+        total_burn: i128,
+    },
+    // The issuer of `denom` did not receive exactly the expected commission on top of its transfers.
+    CommissionNotReceived {
+        denom: String,
+        issuer: String,
+        total_commission: i128,
+        issuer_received: i128,
+    },
+    // The amounts of `denom` are too large to check.
+    Overflow {
+        denom: String,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::SupplyNotConserved {
+                denom,
+                delta_sum,
+                total_burn,
+            } => write!(
+                f,
+                "supply of {} is not conserved: changes sum up to {} but {} is burnt",
+                denom, delta_sum, total_burn
+            ),
+            InvariantViolation::CommissionNotReceived {
+                denom,
+                issuer,
+                total_commission,
+                issuer_received,
+            } => write!(
+                f,
+                "commission of {} is {} but issuer {} received {}",
+                denom, total_commission, issuer, issuer_received
+            ),
+            InvariantViolation::Overflow { denom } => {
+                write!(f, "arithmetic overflow while checking {}", denom)
+            }
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+// Checks for every denom of `multi_send` and `changes` that
+//      - sum(changes) = -total_burn
+//      - change of the issuer = outputs to the issuer - inputs of the issuer + total_commission
+// where total_burn and total_commission are the sums of roundup(rate * taxable_base * input / non_issuer_input_sum)
+// over the inputs of the accounts that are not the issuer. Denoms without a definition have no fees.
+pub fn check_invariants(
+    multi_send: &MultiSend,
+    definitions: &[DenomDefinition],
+    changes: &[Balance],
+) -> Result<(), InvariantViolation> {
+    let mut denoms: BTreeSet<&str> = BTreeSet::new();
+    for balance in multi_send
+        .inputs
+        .iter()
+        .chain(multi_send.outputs.iter())
+        .chain(changes.iter())
+    {
+        for coin in balance.coins.iter() {
+            denoms.insert(&coin.denom);
+        }
+    }
+
+    for denom in denoms {
+        let definition = definitions
+            .iter()
+            .find(|definition| definition.denom == denom);
+        let issuer = definition.map(|definition| definition.issuer.as_str());
+        let overflow = || InvariantViolation::Overflow {
+            denom: denom.to_string(),
+        };
+        let (total_burn, total_commission) = match definition {
+            Some(definition) => expected_fees(multi_send, definition).ok_or_else(overflow)?,
+            None => (0, 0),
+        };
+        let delta_sum = sum_of(changes.iter(), denom, |_| true).ok_or_else(overflow)?;
+        let issuer_delta = sum_of(changes.iter(), denom, |address| Some(address) == issuer)
+            .ok_or_else(overflow)?;
+        let issuer_sent = sum_of(multi_send.inputs.iter(), denom, |address| {
+            Some(address) == issuer
+        })
+        .ok_or_else(overflow)?;
+        let issuer_output = sum_of(multi_send.outputs.iter(), denom, |address| {
+            Some(address) == issuer
+        })
+        .ok_or_else(overflow)?;
+
+        if delta_sum.checked_add(total_burn).ok_or_else(overflow)? != 0 {
+            return Err(InvariantViolation::SupplyNotConserved {
+                denom: denom.to_string(),
+                delta_sum,
+                total_burn,
+            });
+        }
+        let issuer_received = issuer_delta
+            .checked_sub(issuer_output)
+            .and_then(|received| received.checked_add(issuer_sent))
+            .ok_or_else(overflow)?;
+        if issuer_received != total_commission {
+            return Err(InvariantViolation::CommissionNotReceived {
+                denom: denom.to_string(),
+                issuer: issuer.unwrap_or_default().to_string(),
+                total_commission,
+                issuer_received,
+            });
+        }
+    }
+    Ok(())
+}
+
+// The burn and commission of `definition.denom` in `multi_send`, or `None` on overflow. Inputs of the same
+// account are added up first, as the calculation merges them.
+fn expected_fees(multi_send: &MultiSend, definition: &DenomDefinition) -> Option<(i128, i128)> {
+    let is_taxed = |address: &str| address != definition.issuer;
+    let mut inputs: BTreeMap<&str, u128> = BTreeMap::new();
+    for input in multi_send
+        .inputs
+        .iter()
+        .filter(|input| is_taxed(&input.address))
+    {
+        let amount = sum_of(std::iter::once(input), &definition.denom, |_| true)?;
+        let total = inputs.entry(&input.address).or_insert(0);
+        *total = total.checked_add(u128::try_from(amount).ok()?)?;
+    }
+    let input_sum = inputs
+        .values()
+        .try_fold(0u128, |sum, amount| sum.checked_add(*amount))?;
+    let output_sum = sum_of(multi_send.outputs.iter(), &definition.denom, is_taxed)?;
+    let taxable_base = min(input_sum, u128::try_from(output_sum).ok()?);
+    if input_sum == 0 {
+        return Some((0, 0));
+    }
+    let (mut burn, mut commission) = (0u128, 0u128);
+    for amount in inputs.values() {
+        burn = burn.checked_add(definition.burn_rate.checked_share_ceil(
+            taxable_base,
+            *amount,
+            input_sum,
+        )?)?;
+        commission = commission.checked_add(definition.commission_rate.checked_share_ceil(
+            taxable_base,
+            *amount,
+            input_sum,
+        )?)?;
+    }
+    Some((i128::try_from(burn).ok()?, i128::try_from(commission).ok()?))
+}
+
+// The sum of the coins of `denom` of the balances whose address matches `filter`.
+fn sum_of<'a>(
+    balances: impl Iterator<Item = &'a Balance>,
+    denom: &str,
+    filter: impl Fn(&str) -> bool,
+) -> Option<i128> {
+    balances
+        .filter(|balance| filter(&balance.address))
+        .flat_map(|balance| balance.coins.iter())
+        .filter(|coin| coin.denom == denom)
+        .try_fold(0i128, |sum, coin| sum.checked_add(coin.amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+    use crate::fees::UnknownDenomPolicy;
+    use crate::fees::{calculate_balance_changes, calculate_balance_changes_with, Options};
+
+    fn definitions() -> Vec<DenomDefinition> {
+        vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))]
+    }
+
+    // example 2 of the README, where the issuer also receives.
+    fn multi_send() -> MultiSend {
+        MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 650))
+            .input(Balance::new("account2").with_coin("denom1", 350))
+            .output(Balance::new("account_recipient").with_coin("denom1", 500))
+            .output(Balance::new("issuer_account_A").with_coin("denom1", 500))
+    }
+
+    fn changes() -> Vec<Balance> {
+        calculate_balance_changes(
+            vec![
+                Balance::new("account1").with_coin("denom1", 1_000_000),
+                Balance::new("account2").with_coin("denom1", 1_000_000),
+            ],
+            definitions(),
+            multi_send(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn calculated_changes_hold_the_invariants() {
+        assert_eq!(
+            check_invariants(&multi_send(), &definitions(), &changes()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn changes_that_lose_coins_break_supply_conservation() {
+        let mut changes = changes();
+        // account_recipient is credited 499 instead of 500.
+        changes[2].coins[0].amount -= 1;
+
+        assert_eq!(
+            check_invariants(&multi_send(), &definitions(), &changes),
+            Err(InvariantViolation::SupplyNotConserved {
+                denom: "denom1".to_string(),
+                delta_sum: -41,
+                total_burn: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn overcharged_burn_is_detected() {
+        let mut changes = changes();
+        // account1 pays one more, which would look like a burn of 41 if the totals came from the result.
+        changes[0].coins[0].amount -= 1;
+
+        assert_eq!(
+            check_invariants(&multi_send(), &definitions(), &changes),
+            Err(InvariantViolation::SupplyNotConserved {
+                denom: "denom1".to_string(),
+                delta_sum: -41,
+                total_burn: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn commission_kept_from_the_issuer_is_reported() {
+        let mut changes = changes();
+        // the commission is moved from the issuer to account_recipient, which keeps the sum.
+        changes[2].coins[0].amount += 60;
+        changes[3].coins[0].amount -= 60;
+
+        let violation = check_invariants(&multi_send(), &definitions(), &changes).unwrap_err();
+
+        assert_eq!(
+            violation,
+            InvariantViolation::CommissionNotReceived {
+                denom: "denom1".to_string(),
+                issuer: "issuer_account_A".to_string(),
+                total_commission: 60,
+                issuer_received: 0,
+            }
+        );
+        assert_eq!(
+            violation.to_string(),
+            "commission of denom1 is 60 but issuer issuer_account_A received 0"
+        );
+    }
+
+    #[test]
+    fn duplicate_inputs_are_charged_once() {
+        // 1% of 50 + 100 from the same account is roundup(1.5), not roundup(0.5) + roundup(1).
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_commission_rate(Dec::with_prec(1, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 50))
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account2").with_coin("denom1", 150));
+        let changes = calculate_balance_changes(
+            vec![Balance::new("account1").with_coin("denom1", 1000)],
+            definitions.clone(),
+            multi_send.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            check_invariants(&multi_send, &definitions, &changes),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fee_free_denoms_have_no_fees() {
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom9", 100))
+            .output(Balance::new("account2").with_coin("denom9", 100));
+        let mut changes = calculate_balance_changes_with(
+            vec![Balance::new("account1").with_coin("denom9", 100)],
+            Vec::new(),
+            multi_send.clone(),
+            &Options::new().with_unknown_denoms(UnknownDenomPolicy::FeeFree),
+        )
+        .unwrap()
+        .balances;
+        assert_eq!(check_invariants(&multi_send, &[], &changes), Ok(()));
+
+        changes[0].coins[0].amount -= 1;
+
+        assert!(matches!(
+            check_invariants(&multi_send, &[], &changes),
+            Err(InvariantViolation::SupplyNotConserved { total_burn: 0, .. })
+        ));
+    }
+}
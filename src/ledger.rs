@@ -0,0 +1,812 @@
+use crate::error::BalanceChangeError;
+use crate::fees::calculate_balance_changes;
+use crate::operations::{burn, clawback, mint};
+use crate::types::{AccountAmount, Balance, Coin, DenomDefinition, Feature, MultiSend};
+use crate::validation::{check_address_format, check_amount};
+use std::collections::HashMap;
+
+// A bank that owns the account balances and denom definitions, and applies `MultiSend` transactions
+// to them. Changes are applied atomically: a transaction either updates every affected balance or
+// leaves the ledger untouched. The ledger also tracks the total supply and total burnt amount per denom.
+pub struct Ledger {
+    // address -> denom -> amount
+    balances: HashMap<String, HashMap<String, i128>>,
+    definitions: Vec<DenomDefinition>,
+    total_supply: HashMap<String, i128>,
+    total_burnt: HashMap<String, i128>,
+}
+
+impl Ledger {
+    // Creates a ledger holding `balances`. The initial total supply of a denom is the sum of its balances.
+    pub fn new(
+        balances: Vec<Balance>,
+        definitions: Vec<DenomDefinition>,
+    ) -> Result<Ledger, BalanceChangeError> {
+        let mut ledger = Ledger {
+            balances: HashMap::new(),
+            definitions,
+            total_supply: HashMap::new(),
+            total_burnt: HashMap::new(),
+        };
+        for balance in balances {
+            for coin in balance.coins {
+                if coin.amount < 0 {
+                    return Err(BalanceChangeError::InvalidAmount {
+                        address: balance.address,
+                        denom: coin.denom,
+                        amount: coin.amount,
+                    });
+                }
+                let overflow = || BalanceChangeError::Overflow {
+                    denom: coin.denom.clone(),
+                };
+                let amount = ledger
+                    .balances
+                    .entry(balance.address.clone())
+                    .or_default()
+                    .entry(coin.denom.clone())
+                    .or_insert(0);
+                *amount = amount.checked_add(coin.amount).ok_or_else(overflow)?;
+                let supply = ledger.total_supply.entry(coin.denom.clone()).or_insert(0);
+                *supply = supply.checked_add(coin.amount).ok_or_else(overflow)?;
+            }
+        }
+        Ok(ledger)
+    }
+
+    pub fn balance(&self, address: &str, denom: &str) -> i128 {
+        self.balances
+            .get(address)
+            .and_then(|coins| coins.get(denom))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // All non-zero balances, sorted by address and denom.
+    pub fn balances(&self) -> Vec<Balance> {
+        let mut balances: Vec<Balance> = self
+            .balances
+            .iter()
+            .map(|(address, coins)| {
+                let mut coins: Vec<Coin> = coins
+                    .iter()
+                    .filter(|(_, amount)| **amount != 0)
+                    .map(|(denom, amount)| Coin {
+                        denom: denom.clone(),
+                        amount: *amount,
+                    })
+                    .collect();
+                coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+                Balance {
+                    address: address.clone(),
+                    coins,
+                }
+            })
+            .filter(|balance| !balance.coins.is_empty())
+            .collect();
+        balances.sort_by(|a, b| a.address.cmp(&b.address));
+        balances
+    }
+
+    pub fn total_supply(&self, denom: &str) -> i128 {
+        self.total_supply.get(denom).copied().unwrap_or(0)
+    }
+
+    pub fn total_burnt(&self, denom: &str) -> i128 {
+        self.total_burnt.get(denom).copied().unwrap_or(0)
+    }
+
+    // The part of the balance of `address` that the issuer froze.
+    pub fn frozen_balance(&self, address: &str, denom: &str) -> i128 {
+        self.definitions
+            .iter()
+            .find(|definition| definition.denom == denom)
+            .map_or(0, |definition| definition.frozen_amount(address))
+    }
+
+    // Freezes `coin` of the balance of `account` on top of what is already frozen. Only the issuer of a
+    // denom with the freezing feature can freeze, and not its own balance; the frozen amount may be above
+    // the balance.
+    pub fn freeze(
+        &mut self,
+        issuer: &str,
+        account: &str,
+        coin: Coin,
+    ) -> Result<(), BalanceChangeError> {
+        check_address_format(account)?;
+        check_amount(account, &coin)?;
+        let definition = self.issuer_definition(issuer, &coin.denom, Feature::Freezing)?;
+        if account == definition.issuer {
+            return Err(BalanceChangeError::IssuerNotAllowed {
+                address: account.to_string(),
+                denom: coin.denom,
+            });
+        }
+        let frozen = definition
+            .frozen_amount(account)
+            .checked_add(coin.amount)
+            .ok_or(BalanceChangeError::Overflow { denom: coin.denom })?;
+        set_account_amount(&mut definition.frozen, account, frozen);
+        Ok(())
+    }
+
+    // Releases `coin` of the frozen part of the balance of `account`, which must be frozen.
+    pub fn unfreeze(
+        &mut self,
+        issuer: &str,
+        account: &str,
+        coin: Coin,
+    ) -> Result<(), BalanceChangeError> {
+        check_address_format(account)?;
+        check_amount(account, &coin)?;
+        let definition = self.issuer_definition(issuer, &coin.denom, Feature::Freezing)?;
+        let frozen = definition.frozen_amount(account);
+        if coin.amount > frozen {
+            return Err(BalanceChangeError::NotFrozen {
+                address: account.to_string(),
+                denom: coin.denom,
+                amount: coin.amount,
+                frozen,
+            });
+        }
+        set_account_amount(&mut definition.frozen, account, frozen - coin.amount);
+        Ok(())
+    }
+
+    // The most of `denom` that `address` may hold, if the denom has the whitelisting feature.
+    pub fn whitelisted_limit(&self, address: &str, denom: &str) -> i128 {
+        self.definitions
+            .iter()
+            .find(|definition| definition.denom == denom)
+            .map_or(0, |definition| definition.whitelisted_limit(address))
+    }
+
+    // Sets the whitelisted limit of `account` to `coin`, replacing the previous limit. A zero amount removes
+    // the account from the whitelist.
+    pub fn set_whitelisted_limit(
+        &mut self,
+        issuer: &str,
+        account: &str,
+        coin: Coin,
+    ) -> Result<(), BalanceChangeError> {
+        check_address_format(account)?;
+        if coin.amount < 0 {
+            return Err(BalanceChangeError::InvalidAmount {
+                address: account.to_string(),
+                denom: coin.denom,
+                amount: coin.amount,
+            });
+        }
+        let definition = self.issuer_definition(issuer, &coin.denom, Feature::Whitelisting)?;
+        set_account_amount(&mut definition.whitelisted, account, coin.amount);
+        Ok(())
+    }
+
+    pub fn is_globally_frozen(&self, denom: &str) -> bool {
+        self.definitions
+            .iter()
+            .any(|definition| definition.denom == denom && definition.is_globally_frozen())
+    }
+
+    // Halts all transfers of `denom` between accounts other than the issuer.
+    pub fn global_freeze(&mut self, issuer: &str, denom: &str) -> Result<(), BalanceChangeError> {
+        self.issuer_definition(issuer, denom, Feature::Freezing)?
+            .globally_frozen = true;
+        Ok(())
+    }
+
+    pub fn global_unfreeze(&mut self, issuer: &str, denom: &str) -> Result<(), BalanceChangeError> {
+        self.issuer_definition(issuer, denom, Feature::Freezing)?
+            .globally_frozen = false;
+        Ok(())
+    }
+
+    // The definition of `denom` for an operation of `caller` that needs `feature` and the issuer role.
+    fn issuer_definition(
+        &mut self,
+        caller: &str,
+        denom: &str,
+        feature: Feature,
+    ) -> Result<&mut DenomDefinition, BalanceChangeError> {
+        let definition = self
+            .definitions
+            .iter_mut()
+            .find(|definition| definition.denom == denom)
+            .ok_or_else(|| BalanceChangeError::UnknownDenom {
+                denom: denom.to_string(),
+            })?;
+        if !definition.has_feature(feature) {
+            return Err(BalanceChangeError::FeatureDisabled {
+                denom: denom.to_string(),
+                feature,
+            });
+        }
+        if definition.issuer != caller {
+            return Err(BalanceChangeError::Unauthorized {
+                address: caller.to_string(),
+                denom: denom.to_string(),
+            });
+        }
+        Ok(definition)
+    }
+
+    // Calculates the balance changes of `multi_send` against the current balances and applies them.
+    // Returns the applied changes. On error nothing is changed.
+    pub fn execute(&mut self, multi_send: MultiSend) -> Result<Vec<Balance>, BalanceChangeError> {
+        let changes =
+            calculate_balance_changes(self.balances(), self.definitions.clone(), multi_send)?;
+        self.apply(&changes)?;
+        Ok(changes)
+    }
+
+    // Creates `coin` for `recipient`, see `operations::mint`. The total supply grows by the minted amount.
+    pub fn mint(
+        &mut self,
+        issuer: &str,
+        coin: Coin,
+        recipient: &str,
+    ) -> Result<Vec<Balance>, BalanceChangeError> {
+        let changes = mint(&self.balances(), &self.definitions, issuer, coin, recipient)?;
+        self.apply(&changes)?;
+        Ok(changes)
+    }
+
+    // Destroys `coin` of the balance of `holder`, see `operations::burn`. It counts as burnt like the burn
+    // of transfers.
+    pub fn burn(&mut self, holder: &str, coin: Coin) -> Result<Vec<Balance>, BalanceChangeError> {
+        let changes = burn(&self.balances(), &self.definitions, holder, coin)?;
+        self.apply(&changes)?;
+        Ok(changes)
+    }
+
+    // Takes `coin` from `holder` back to the issuer, see `operations::clawback`. The total supply does not change.
+    pub fn clawback(
+        &mut self,
+        issuer: &str,
+        holder: &str,
+        coin: Coin,
+    ) -> Result<Vec<Balance>, BalanceChangeError> {
+        let changes = clawback(&self.balances(), &self.definitions, issuer, holder, coin)?;
+        self.apply(&changes)?;
+        Ok(changes)
+    }
+
+    // Applies balance changes atomically. What the changes of a denom add up to is created (positive) or
+    // burnt (negative), and the total supply follows.
+    fn apply(&mut self, changes: &[Balance]) -> Result<(), BalanceChangeError> {
+        // Stage every new balance and the created amount per denom before touching the ledger.
+        let mut staged: HashMap<(String, String), i128> = HashMap::new();
+        let mut created: HashMap<String, i128> = HashMap::new();
+        for balance in changes.iter() {
+            for coin in balance.coins.iter() {
+                let overflow = || BalanceChangeError::Overflow {
+                    denom: coin.denom.clone(),
+                };
+                let key = (balance.address.clone(), coin.denom.clone());
+                let current = self.balance(&balance.address, &coin.denom);
+                let amount = staged.entry(key).or_insert(current);
+                *amount = amount.checked_add(coin.amount).ok_or_else(overflow)?;
+                if *amount < 0 {
+                    return Err(BalanceChangeError::InsufficientFunds {
+                        address: balance.address.clone(),
+                        denom: coin.denom.clone(),
+                        required: current.saturating_sub(*amount),
+                        available: current,
+                    });
+                }
+                let created = created.entry(coin.denom.clone()).or_insert(0);
+                *created = created.checked_add(coin.amount).ok_or_else(overflow)?;
+            }
+        }
+        let mut supply_updates: Vec<(String, i128, i128)> = Vec::new();
+        for (denom, amount) in created {
+            let overflow = || BalanceChangeError::Overflow {
+                denom: denom.clone(),
+            };
+            let supply = self
+                .total_supply(&denom)
+                .checked_add(amount)
+                .ok_or_else(overflow)?;
+            // whatever is not credited to an account is burnt.
+            let total_burnt = self
+                .total_burnt(&denom)
+                .checked_sub(amount.min(0))
+                .ok_or_else(overflow)?;
+            supply_updates.push((denom, supply, total_burnt));
+        }
+
+        for ((address, denom), amount) in staged {
+            self.balances
+                .entry(address)
+                .or_default()
+                .insert(denom, amount);
+        }
+        for (denom, supply, total_burnt) in supply_updates {
+            self.total_supply.insert(denom.clone(), supply);
+            self.total_burnt.insert(denom, total_burnt);
+        }
+        Ok(())
+    }
+}
+
+// Sets the amount of `address` in `amounts`, dropping the entry if it is zero.
+fn set_account_amount(amounts: &mut Vec<AccountAmount>, address: &str, amount: i128) {
+    amounts.retain(|entry| entry.address != address);
+    if amount > 0 {
+        amounts.push(AccountAmount {
+            address: address.to_string(),
+            amount,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+
+    fn definitions() -> Vec<DenomDefinition> {
+        vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))]
+    }
+
+    fn balances() -> Vec<Balance> {
+        vec![
+            Balance::new("account1").with_coin("denom1", 10_000),
+            Balance::new("account2").with_coin("denom1", 100),
+        ]
+    }
+
+    fn send(from: &str, to: &str, amount: i128) -> MultiSend {
+        MultiSend::new()
+            .input(Balance::new(from).with_coin("denom1", amount))
+            .output(Balance::new(to).with_coin("denom1", amount))
+    }
+
+    #[test]
+    fn execute_applies_changes_and_tracks_supply() {
+        let mut ledger = Ledger::new(balances(), definitions()).unwrap();
+        assert_eq!(ledger.total_supply("denom1"), 10_100);
+
+        ledger.execute(send("account1", "account3", 1000)).unwrap();
+
+        assert_eq!(ledger.balance("account1", "denom1"), 8_800);
+        assert_eq!(ledger.balance("account3", "denom1"), 1_000);
+        assert_eq!(ledger.balance("issuer_account_A", "denom1"), 120);
+        assert_eq!(ledger.total_burnt("denom1"), 80);
+        assert_eq!(ledger.total_supply("denom1"), 10_020);
+
+        ledger.execute(send("account3", "account1", 500)).unwrap();
+
+        assert_eq!(ledger.balance("account3", "denom1"), 400);
+        assert_eq!(ledger.total_burnt("denom1"), 120);
+        assert_eq!(ledger.total_supply("denom1"), 9_980);
+        let held: i128 = ledger
+            .balances()
+            .iter()
+            .flat_map(|balance| balance.coins.iter())
+            .map(|coin| coin.amount)
+            .sum();
+        assert_eq!(held, ledger.total_supply("denom1"));
+    }
+
+    #[test]
+    fn rejected_transaction_leaves_ledger_untouched() {
+        let mut ledger = Ledger::new(balances(), definitions()).unwrap();
+
+        let result = ledger.execute(send("account2", "account3", 100));
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                required: 120,
+                available: 100,
+            })
+        );
+        assert_eq!(ledger.balances(), balances());
+        assert_eq!(ledger.total_supply("denom1"), 10_100);
+        assert_eq!(ledger.total_burnt("denom1"), 0);
+    }
+
+    #[test]
+    fn negative_balance_is_never_applied() {
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")];
+        let mut ledger = Ledger::new(balances(), definitions).unwrap();
+        // each input alone fits the balance of account2, together they do not.
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account2").with_coin("denom1", 60))
+            .input(Balance::new("account2").with_coin("denom1", 60))
+            .output(Balance::new("account3").with_coin("denom1", 120));
+
+        let result = ledger.execute(multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                required: 120,
+                available: 100,
+            })
+        );
+        assert_eq!(ledger.balances(), balances());
+        assert_eq!(ledger.balance("account3", "denom1"), 0);
+    }
+
+    #[test]
+    fn negative_opening_balance_is_rejected() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", -1)];
+
+        assert!(matches!(
+            Ledger::new(balances, definitions()),
+            Err(BalanceChangeError::InvalidAmount { amount: -1, .. })
+        ));
+    }
+
+    fn freezable() -> Vec<DenomDefinition> {
+        vec![DenomDefinition::new("denom1", "issuer_account_A").with_feature(Feature::Freezing)]
+    }
+
+    #[test]
+    fn frozen_part_of_a_balance_can_not_be_sent() {
+        let mut ledger = Ledger::new(balances(), freezable()).unwrap();
+        ledger
+            .freeze("issuer_account_A", "account1", Coin::new("denom1", 9_500))
+            .unwrap();
+
+        ledger.execute(send("account1", "account3", 500)).unwrap();
+        let result = ledger.execute(send("account1", "account3", 1));
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::FrozenFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 1,
+                spendable: 0,
+            })
+        );
+        assert_eq!(ledger.balance("account1", "denom1"), 9_500);
+        assert_eq!(ledger.frozen_balance("account1", "denom1"), 9_500);
+    }
+
+    #[test]
+    fn unfrozen_balance_can_be_sent_again() {
+        let mut ledger = Ledger::new(balances(), freezable()).unwrap();
+        ledger
+            .freeze("issuer_account_A", "account2", Coin::new("denom1", 100))
+            .unwrap();
+        assert!(ledger.execute(send("account2", "account3", 50)).is_err());
+
+        ledger
+            .unfreeze("issuer_account_A", "account2", Coin::new("denom1", 60))
+            .unwrap();
+
+        assert_eq!(ledger.frozen_balance("account2", "denom1"), 40);
+        assert!(ledger.execute(send("account2", "account3", 60)).is_ok());
+        assert_eq!(
+            ledger.unfreeze("issuer_account_A", "account2", Coin::new("denom1", 41)),
+            Err(BalanceChangeError::NotFrozen {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                amount: 41,
+                frozen: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn only_the_issuer_of_a_freezable_denom_can_freeze() {
+        let mut ledger = Ledger::new(balances(), freezable()).unwrap();
+
+        assert_eq!(
+            ledger.freeze("account2", "account1", Coin::new("denom1", 100)),
+            Err(BalanceChangeError::Unauthorized {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+
+        let mut ledger = Ledger::new(balances(), definitions()).unwrap();
+
+        assert_eq!(
+            ledger.freeze("issuer_account_A", "account1", Coin::new("denom1", 100)),
+            Err(BalanceChangeError::FeatureDisabled {
+                denom: "denom1".to_string(),
+                feature: Feature::Freezing,
+            })
+        );
+        assert_eq!(ledger.frozen_balance("account1", "denom1"), 0);
+    }
+
+    #[test]
+    fn receiving_is_not_limited_by_frozen_funds() {
+        let mut ledger = Ledger::new(balances(), freezable()).unwrap();
+        ledger
+            .freeze("issuer_account_A", "account3", Coin::new("denom1", 1_000))
+            .unwrap();
+
+        ledger.execute(send("account1", "account3", 500)).unwrap();
+
+        assert_eq!(ledger.balance("account3", "denom1"), 500);
+    }
+
+    fn two_denoms() -> (Vec<Balance>, Vec<DenomDefinition>) {
+        let balances = vec![
+            Balance::new("account1")
+                .with_coin("denom1", 1000)
+                .with_coin("denom2", 1000),
+            Balance::new("issuer_account_A").with_coin("denom1", 1000),
+        ];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A").with_feature(Feature::Freezing),
+            DenomDefinition::new("denom2", "issuer_account_B"),
+        ];
+        (balances, definitions)
+    }
+
+    #[test]
+    fn globally_frozen_denom_can_not_move_between_holders() {
+        let (balances, definitions) = two_denoms();
+        let mut ledger = Ledger::new(balances.clone(), definitions).unwrap();
+        ledger.global_freeze("issuer_account_A", "denom1").unwrap();
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 100)
+                    .with_coin("denom2", 100),
+            )
+            .output(
+                Balance::new("account2")
+                    .with_coin("denom1", 100)
+                    .with_coin("denom2", 100),
+            );
+
+        let result = ledger.execute(multi_send);
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::GloballyFrozen {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+        assert_eq!(ledger.balances(), balances);
+        // the other denom of the transaction is not frozen.
+        let denom2 = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom2", 100))
+            .output(Balance::new("account2").with_coin("denom2", 100));
+        assert!(ledger.execute(denom2).is_ok());
+    }
+
+    #[test]
+    fn issuer_moves_its_globally_frozen_denom() {
+        let (balances, definitions) = two_denoms();
+        let mut ledger = Ledger::new(balances, definitions).unwrap();
+        ledger.global_freeze("issuer_account_A", "denom1").unwrap();
+        assert!(ledger.is_globally_frozen("denom1"));
+
+        let multi_send = MultiSend::new()
+            .input(Balance::new("issuer_account_A").with_coin("denom1", 100))
+            .input(Balance::new("account1").with_coin("denom2", 100))
+            .output(
+                Balance::new("account2")
+                    .with_coin("denom1", 100)
+                    .with_coin("denom2", 100),
+            );
+        ledger.execute(multi_send).unwrap();
+
+        assert_eq!(ledger.balance("account2", "denom1"), 100);
+        assert_eq!(ledger.balance("account2", "denom2"), 100);
+    }
+
+    #[test]
+    fn global_freeze_is_toggled_by_the_issuer() {
+        let (balances, definitions) = two_denoms();
+        let mut ledger = Ledger::new(balances, definitions).unwrap();
+
+        assert_eq!(
+            ledger.global_freeze("account1", "denom1"),
+            Err(BalanceChangeError::Unauthorized {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+        assert_eq!(
+            ledger.global_freeze("issuer_account_B", "denom2"),
+            Err(BalanceChangeError::FeatureDisabled {
+                denom: "denom2".to_string(),
+                feature: Feature::Freezing,
+            })
+        );
+        ledger.global_freeze("issuer_account_A", "denom1").unwrap();
+        assert!(ledger.execute(send("account1", "account2", 10)).is_err());
+
+        ledger
+            .global_unfreeze("issuer_account_A", "denom1")
+            .unwrap();
+
+        assert!(!ledger.is_globally_frozen("denom1"));
+        assert!(ledger.execute(send("account1", "account2", 10)).is_ok());
+    }
+
+    fn whitelisted() -> Vec<DenomDefinition> {
+        vec![DenomDefinition::new("denom1", "issuer_account_A").with_feature(Feature::Whitelisting)]
+    }
+
+    #[test]
+    fn recipient_can_not_exceed_its_whitelisted_limit() {
+        let mut ledger = Ledger::new(balances(), whitelisted()).unwrap();
+        ledger
+            .set_whitelisted_limit("issuer_account_A", "account2", Coin::new("denom1", 300))
+            .unwrap();
+
+        ledger.execute(send("account1", "account2", 200)).unwrap();
+        let result = ledger.execute(send("account1", "account2", 1));
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::WhitelistedLimitExceeded {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+                balance: 301,
+                limit: 300,
+            })
+        );
+        assert_eq!(ledger.balance("account2", "denom1"), 300);
+        // account3 is not whitelisted at all.
+        assert!(matches!(
+            ledger.execute(send("account1", "account3", 1)),
+            Err(BalanceChangeError::WhitelistedLimitExceeded { limit: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn issuer_is_exempt_from_whitelisting() {
+        let mut ledger = Ledger::new(balances(), whitelisted()).unwrap();
+
+        ledger
+            .execute(send("account1", "issuer_account_A", 5_000))
+            .unwrap();
+
+        assert_eq!(ledger.balance("issuer_account_A", "denom1"), 5_000);
+    }
+
+    #[test]
+    fn only_the_issuer_sets_whitelisted_limits() {
+        let mut ledger = Ledger::new(balances(), whitelisted()).unwrap();
+
+        assert_eq!(
+            ledger.set_whitelisted_limit("account1", "account1", Coin::new("denom1", 100)),
+            Err(BalanceChangeError::Unauthorized {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+        ledger
+            .set_whitelisted_limit("issuer_account_A", "account3", Coin::new("denom1", 100))
+            .unwrap();
+        ledger
+            .set_whitelisted_limit("issuer_account_A", "account3", Coin::new("denom1", 50))
+            .unwrap();
+        assert_eq!(ledger.whitelisted_limit("account3", "denom1"), 50);
+    }
+
+    #[test]
+    fn supply_follows_mints_burns_and_transfer_burns() {
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_feature(Feature::Minting)
+            .with_feature(Feature::Burning)];
+        let mut ledger = Ledger::new(balances(), definitions).unwrap();
+
+        let minted = ledger
+            .mint("issuer_account_A", Coin::new("denom1", 1_000), "account3")
+            .unwrap();
+        ledger.execute(send("account3", "account2", 500)).unwrap();
+        let burnt = ledger.burn("account2", Coin::new("denom1", 300)).unwrap();
+
+        assert_eq!(
+            minted,
+            vec![Balance::new("account3").with_coin("denom1", 1_000)]
+        );
+        assert_eq!(
+            burnt,
+            vec![Balance::new("account2").with_coin("denom1", -300)]
+        );
+        // 10_100 + 1_000 minted - 40 burnt by the transfer - 300 burnt by account2.
+        assert_eq!(ledger.total_supply("denom1"), 10_760);
+        assert_eq!(ledger.total_burnt("denom1"), 340);
+        let held: i128 = ledger
+            .balances()
+            .iter()
+            .flat_map(|balance| balance.coins.iter())
+            .map(|coin| coin.amount)
+            .sum();
+        assert_eq!(held, ledger.total_supply("denom1"));
+    }
+
+    #[test]
+    fn rejected_mint_leaves_supply_untouched() {
+        let mut ledger = Ledger::new(balances(), definitions()).unwrap();
+
+        let result = ledger.mint("issuer_account_A", Coin::new("denom1", 1_000), "account3");
+
+        assert!(matches!(
+            result,
+            Err(BalanceChangeError::FeatureDisabled {
+                feature: Feature::Minting,
+                ..
+            })
+        ));
+        assert_eq!(ledger.total_supply("denom1"), 10_100);
+        assert_eq!(ledger.balances(), balances());
+    }
+
+    #[test]
+    fn clawback_keeps_the_supply() {
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_feature(Feature::Clawback)];
+        let mut ledger = Ledger::new(balances(), definitions).unwrap();
+
+        ledger
+            .clawback("issuer_account_A", "account2", Coin::new("denom1", 100))
+            .unwrap();
+
+        assert_eq!(ledger.balance("account2", "denom1"), 0);
+        assert_eq!(ledger.balance("issuer_account_A", "denom1"), 100);
+        assert_eq!(ledger.total_supply("denom1"), 10_100);
+        assert_eq!(ledger.total_burnt("denom1"), 0);
+        assert!(ledger
+            .clawback("issuer_account_A", "account2", Coin::new("denom1", 1))
+            .is_err());
+    }
+
+    #[test]
+    fn issuer_can_not_freeze_its_own_balance() {
+        let mut ledger = Ledger::new(balances(), freezable()).unwrap();
+
+        assert_eq!(
+            ledger.freeze(
+                "issuer_account_A",
+                "issuer_account_A",
+                Coin::new("denom1", 100)
+            ),
+            Err(BalanceChangeError::IssuerNotAllowed {
+                address: "issuer_account_A".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+        assert_eq!(ledger.frozen_balance("issuer_account_A", "denom1"), 0);
+    }
+
+    #[test]
+    fn unfreeze_checks_the_address() {
+        let mut ledger = Ledger::new(balances(), freezable()).unwrap();
+
+        assert_eq!(
+            ledger.unfreeze("issuer_account_A", "", Coin::new("denom1", 100)),
+            Err(BalanceChangeError::InvalidAddress {
+                address: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn holders_send_to_the_issuer_during_a_global_freeze() {
+        let (balances, definitions) = two_denoms();
+        let mut ledger = Ledger::new(balances, definitions).unwrap();
+        ledger.global_freeze("issuer_account_A", "denom1").unwrap();
+
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("issuer_account_A").with_coin("denom1", 100));
+        ledger.execute(multi_send).unwrap();
+
+        assert_eq!(ledger.balance("issuer_account_A", "denom1"), 1100);
+    }
+}
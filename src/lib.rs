@@ -0,0 +1,38 @@
+// Balance changes of cosmos-style `MultiSend` transactions with burn and commission rates.
+//
+// `calculate_balance_changes` computes the changes of one transaction without applying them,
+// `Ledger` and `UtxoSet` keep state across transactions.
+
+#[macro_use]
+mod trace;
+
+pub mod allocation;
+pub mod decimal;
+pub mod error;
+pub mod fees;
+pub mod invariants;
+pub mod ledger;
+mod math;
+pub mod operations;
+pub mod quote;
+pub mod report;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod types;
+pub mod utxo;
+pub mod validation;
+
+pub use decimal::Dec;
+pub use error::BalanceChangeError;
+pub use fees::{
+    calculate_balance_changes, calculate_balance_changes_with, BalanceChanges, FundsPolicy,
+    Options, UnknownDenomPolicy,
+};
+pub use invariants::{check_invariants, InvariantViolation};
+pub use ledger::Ledger;
+pub use operations::{burn, clawback, mint};
+pub use quote::{gross_up, gross_up_multi, max_sendable, GrossUp};
+pub use report::BalanceChangeReport;
+pub use types::{AccountAmount, Balance, Coin, DenomDefinition, Feature, MultiSend};
+pub use utxo::UtxoSet;
+pub use validation::DuplicatePolicy;
@@ -0,0 +1,161 @@
+// 256-bit intermediates for the fee math. A rate in atomics (up to 10^18) times an amount (up to i128::MAX)
+// does not fit into a u128, while the result (a share of the amount) always does.
+
+const LOW_MASK: u128 = u64::MAX as u128;
+
+// An unsigned 256-bit integer, just wide enough for the product of two u128s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct U256 {
+    // the fields are compared in order, so `hi` has to come first for `Ord`.
+    hi: u128,
+    lo: u128,
+}
+
+impl U256 {
+    pub const ZERO: U256 = U256 { hi: 0, lo: 0 };
+
+    // The full product `a * b`, which never overflows.
+    pub fn mul(a: u128, b: u128) -> U256 {
+        let (a_hi, a_lo) = (a >> 64, a & LOW_MASK);
+        let (b_hi, b_lo) = (b >> 64, b & LOW_MASK);
+        let low = a_lo * b_lo;
+        let cross_1 = a_lo * b_hi;
+        let cross_2 = a_hi * b_lo;
+        // three values below 2^64 each, so the sum fits into a u128.
+        let middle = (low >> 64) + (cross_1 & LOW_MASK) + (cross_2 & LOW_MASK);
+        U256 {
+            hi: a_hi * b_hi + (cross_1 >> 64) + (cross_2 >> 64) + (middle >> 64),
+            lo: (low & LOW_MASK) | (middle << 64),
+        }
+    }
+
+    pub fn checked_add(self, other: U256) -> Option<U256> {
+        let (lo, carry) = self.lo.overflowing_add(other.lo);
+        let hi = self.hi.checked_add(other.hi)?.checked_add(carry as u128)?;
+        Some(U256 { hi, lo })
+    }
+
+    // Quotient and remainder of the division by `divisor`, or `None` if `divisor` is zero.
+    pub fn checked_div_rem(self, divisor: u128) -> Option<(U256, u128)> {
+        if divisor == 0 {
+            return None;
+        }
+        // schoolbook division, one bit at a time.
+        let mut quotient = U256::ZERO;
+        let mut remainder: u128 = 0;
+        for bit in (0..256).rev() {
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | self.bit(bit);
+            quotient = quotient.shl1();
+            // with the carry the shifted remainder is above 2^128 and therefore above the divisor.
+            if carry == 1 || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient.lo |= 1;
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    pub fn to_u128(self) -> Option<u128> {
+        if self.hi == 0 {
+            Some(self.lo)
+        } else {
+            None
+        }
+    }
+
+    fn bit(self, index: u32) -> u128 {
+        if index >= 128 {
+            (self.hi >> (index - 128)) & 1
+        } else {
+            (self.lo >> index) & 1
+        }
+    }
+
+    fn shl1(self) -> U256 {
+        U256 {
+            hi: (self.hi << 1) | (self.lo >> 127),
+            lo: self.lo << 1,
+        }
+    }
+}
+
+// floor(a * b / c), or `None` if `c` is zero or the result does not fit into a u128.
+pub fn mul_div_floor(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (quotient, _) = U256::mul(a, b).checked_div_rem(c)?;
+    quotient.to_u128()
+}
+
+// ceil(a * b / c), or `None` if `c` is zero or the result does not fit into a u128.
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (quotient, remainder) = U256::mul(a, b).checked_div_rem(c)?;
+    let quotient = quotient.to_u128()?;
+    if remainder == 0 {
+        Some(quotient)
+    } else {
+        quotient.checked_add(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_product_of_two_u128() {
+        assert_eq!(
+            U256::mul(u128::MAX, u128::MAX),
+            U256 {
+                hi: u128::MAX - 1,
+                lo: 1,
+            }
+        );
+        assert_eq!(U256::mul(1 << 64, 1 << 64), U256 { hi: 1, lo: 0 });
+        assert_eq!(U256::mul(12, 34), U256 { hi: 0, lo: 408 });
+    }
+
+    #[test]
+    fn division_with_remainder() {
+        let product = U256::mul(u128::MAX, u128::MAX);
+
+        assert_eq!(
+            product.checked_div_rem(u128::MAX),
+            Some((
+                U256 {
+                    hi: 0,
+                    lo: u128::MAX
+                },
+                0
+            ))
+        );
+        assert_eq!(
+            U256 { hi: 0, lo: 1001 }.checked_div_rem(10),
+            Some((U256 { hi: 0, lo: 100 }, 1))
+        );
+        assert_eq!(product.checked_div_rem(0), None);
+    }
+
+    #[test]
+    fn checked_add_detects_overflow() {
+        let max = U256::mul(u128::MAX, u128::MAX)
+            .checked_add(U256::mul(u128::MAX, 2))
+            .unwrap();
+
+        assert_eq!(max.to_u128(), None);
+        assert_eq!(max.checked_add(U256 { hi: 0, lo: 1 }), None);
+    }
+
+    #[test]
+    fn mul_div_rounds_as_asked() {
+        let amount = i128::MAX as u128;
+        let rate = 80_000_000_000_000_000;
+        let one = 1_000_000_000_000_000_000;
+
+        // 8% of i128::MAX, whose product with the rate needs more than 128 bits.
+        assert_eq!(mul_div_floor(rate, amount, one), Some(amount / 100 * 8 + 2));
+        assert_eq!(mul_div_ceil(rate, amount, one), Some(amount / 100 * 8 + 3));
+        assert_eq!(mul_div_ceil(10, 10, 5), Some(20));
+        assert_eq!(mul_div_floor(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div_ceil(1, 1, 0), None);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::error::BalanceChangeError;
+use crate::fees::{balance_of, canonicalize, check_funds, check_whitelisting};
+use crate::types::{Balance, Coin, DenomDefinition, Feature};
+use crate::validation::{check_address_format, check_amount, find_definition};
+
+// Issuer operations next to `MultiSend`. Like `calculate_balance_changes` they only compute the balance
+// changes, in the same canonical form, and leave applying them to the caller.
+
+// Creates `coin` for `recipient`, whose balance in `original_balances` must stay within its whitelisted
+// limit. Only the issuer of a denom with the minting feature can mint.
+pub fn mint(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    issuer: &str,
+    coin: Coin,
+    recipient: &str,
+) -> Result<Vec<Balance>, BalanceChangeError> {
+    check_address_format(recipient)?;
+    check_amount(recipient, &coin)?;
+    let definition = find_definition(definitions, &coin.denom)?;
+    if !definition.has_feature(Feature::Minting) {
+        return Err(BalanceChangeError::FeatureDisabled {
+            denom: coin.denom,
+            feature: Feature::Minting,
+        });
+    }
+    if definition.issuer != issuer {
+        return Err(BalanceChangeError::Unauthorized {
+            address: issuer.to_string(),
+            denom: coin.denom,
+        });
+    }
+    let changes = vec![Balance::new(recipient).with_coin(coin.denom, coin.amount)];
+    check_whitelisting(original_balances, definitions, &changes, &changes)?;
+    Ok(changes)
+}
+
+// Destroys `coin` of the balance of `holder` in `original_balances`. As in Coreum's asset module the issuer can
+// always burn its own tokens, other holders only if the denom has the burning feature. Frozen funds can not be
+// burnt.
+pub fn burn(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    holder: &str,
+    coin: Coin,
+) -> Result<Vec<Balance>, BalanceChangeError> {
+    check_address_format(holder)?;
+    check_amount(holder, &coin)?;
+    let definition = find_definition(definitions, &coin.denom)?;
+    if definition.issuer != holder {
+        if !definition.has_feature(Feature::Burning) {
+            return Err(BalanceChangeError::FeatureDisabled {
+                denom: coin.denom,
+                feature: Feature::Burning,
+            });
+        }
+        if definition.is_globally_frozen() {
+            return Err(BalanceChangeError::GloballyFrozen {
+                address: holder.to_string(),
+                denom: coin.denom,
+            });
+        }
+    }
+    check_funds(
+        original_balances,
+        definitions,
+        holder,
+        &coin.denom,
+        coin.amount,
+    )?;
+    Ok(vec![
+        Balance::new(holder).with_coin(coin.denom, -coin.amount)
+    ])
+}
+
+// Moves `coin` from `holder` back to the issuer, without burn and commission. Only the issuer of a denom with
+// the clawback feature can claw back, frozen funds included, and not from itself.
+pub fn clawback(
+    original_balances: &[Balance],
+    definitions: &[DenomDefinition],
+    issuer: &str,
+    holder: &str,
+    coin: Coin,
+) -> Result<Vec<Balance>, BalanceChangeError> {
+    check_address_format(holder)?;
+    check_amount(holder, &coin)?;
+    let definition = find_definition(definitions, &coin.denom)?;
+    if !definition.has_feature(Feature::Clawback) {
+        return Err(BalanceChangeError::FeatureDisabled {
+            denom: coin.denom,
+            feature: Feature::Clawback,
+        });
+    }
+    if definition.issuer != issuer {
+        return Err(BalanceChangeError::Unauthorized {
+            address: issuer.to_string(),
+            denom: coin.denom,
+        });
+    }
+    if holder == issuer {
+        return Err(BalanceChangeError::IssuerNotAllowed {
+            address: holder.to_string(),
+            denom: coin.denom,
+        });
+    }
+    let available = balance_of(original_balances, holder, &coin.denom);
+    if coin.amount > available {
+        return Err(BalanceChangeError::InsufficientFunds {
+            address: holder.to_string(),
+            denom: coin.denom,
+            required: coin.amount,
+            available,
+        });
+    }
+    canonicalize(vec![
+        Balance::new(holder).with_coin(coin.denom.clone(), -coin.amount),
+        Balance::new(issuer).with_coin(coin.denom, coin.amount),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+    use crate::types::AccountAmount;
+
+    fn definitions() -> Vec<DenomDefinition> {
+        vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_feature(Feature::Minting)
+                .with_feature(Feature::Burning)
+                .with_feature(Feature::Freezing)
+                .with_feature(Feature::Clawback),
+            DenomDefinition::new("denom2", "issuer_account_B"),
+        ]
+    }
+
+    #[test]
+    fn issuer_mints_to_a_recipient() {
+        let changes = mint(
+            &[],
+            &definitions(),
+            "issuer_account_A",
+            Coin::new("denom1", 500),
+            "account1",
+        );
+
+        assert_eq!(
+            changes,
+            Ok(vec![Balance::new("account1").with_coin("denom1", 500)])
+        );
+    }
+
+    #[test]
+    fn mint_needs_the_feature_and_the_issuer() {
+        assert_eq!(
+            mint(
+                &[],
+                &definitions(),
+                "account1",
+                Coin::new("denom1", 500),
+                "account1"
+            ),
+            Err(BalanceChangeError::Unauthorized {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+        assert_eq!(
+            mint(
+                &[],
+                &definitions(),
+                "issuer_account_B",
+                Coin::new("denom2", 500),
+                "account1"
+            ),
+            Err(BalanceChangeError::FeatureDisabled {
+                denom: "denom2".to_string(),
+                feature: Feature::Minting,
+            })
+        );
+    }
+
+    #[test]
+    fn holder_burns_spendable_funds() {
+        let mut definitions = definitions();
+        definitions[0].frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 60,
+        });
+        let balances = vec![Balance::new("account1").with_coin("denom1", 100)];
+
+        assert_eq!(
+            burn(&balances, &definitions, "account1", Coin::new("denom1", 40)),
+            Ok(vec![Balance::new("account1").with_coin("denom1", -40)])
+        );
+        assert_eq!(
+            burn(&balances, &definitions, "account1", Coin::new("denom1", 41)),
+            Err(BalanceChangeError::FrozenFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 41,
+                spendable: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn only_the_issuer_burns_without_the_feature() {
+        let balances = vec![
+            Balance::new("account1").with_coin("denom2", 100),
+            Balance::new("issuer_account_B").with_coin("denom2", 100),
+        ];
+
+        assert_eq!(
+            burn(
+                &balances,
+                &definitions(),
+                "account1",
+                Coin::new("denom2", 10)
+            ),
+            Err(BalanceChangeError::FeatureDisabled {
+                denom: "denom2".to_string(),
+                feature: Feature::Burning,
+            })
+        );
+        assert_eq!(
+            burn(
+                &balances,
+                &definitions(),
+                "issuer_account_B",
+                Coin::new("denom2", 101)
+            ),
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "issuer_account_B".to_string(),
+                denom: "denom2".to_string(),
+                required: 101,
+                available: 100,
+            })
+        );
+        assert!(burn(
+            &balances,
+            &definitions(),
+            "issuer_account_B",
+            Coin::new("denom2", 100)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn mint_respects_whitelisted_limits() {
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_feature(Feature::Minting)
+            .with_feature(Feature::Whitelisting)];
+
+        let result = mint(
+            &[],
+            &definitions,
+            "issuer_account_A",
+            Coin::new("denom1", 1),
+            "account1",
+        );
+
+        assert!(matches!(
+            result,
+            Err(BalanceChangeError::WhitelistedLimitExceeded { limit: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn clawback_moves_frozen_funds_to_the_issuer_without_fees() {
+        let mut definitions = definitions();
+        definitions[0].burn_rate = Dec::with_prec(1, 1);
+        definitions[0].frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 100,
+        });
+        let balances = vec![Balance::new("account1").with_coin("denom1", 100)];
+
+        let changes = clawback(
+            &balances,
+            &definitions,
+            "issuer_account_A",
+            "account1",
+            Coin::new("denom1", 100),
+        );
+
+        assert_eq!(
+            changes,
+            Ok(vec![
+                Balance::new("account1").with_coin("denom1", -100),
+                Balance::new("issuer_account_A").with_coin("denom1", 100),
+            ])
+        );
+    }
+
+    #[test]
+    fn clawback_is_rejected_when_disabled_or_not_covered() {
+        let balances = vec![Balance::new("account1")
+            .with_coin("denom1", 100)
+            .with_coin("denom2", 100)];
+
+        assert_eq!(
+            clawback(
+                &balances,
+                &definitions(),
+                "issuer_account_B",
+                "account1",
+                Coin::new("denom2", 10)
+            ),
+            Err(BalanceChangeError::FeatureDisabled {
+                denom: "denom2".to_string(),
+                feature: Feature::Clawback,
+            })
+        );
+        assert_eq!(
+            clawback(
+                &balances,
+                &definitions(),
+                "account2",
+                "account1",
+                Coin::new("denom1", 10)
+            ),
+            Err(BalanceChangeError::Unauthorized {
+                address: "account2".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+        assert_eq!(
+            clawback(
+                &balances,
+                &definitions(),
+                "issuer_account_A",
+                "account1",
+                Coin::new("denom1", 101)
+            ),
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 101,
+                available: 100,
+            })
+        );
+    }
+
+    #[test]
+    fn issuer_can_not_claw_back_from_itself() {
+        let balances = vec![Balance::new("issuer_account_A").with_coin("denom1", 100)];
+
+        assert_eq!(
+            clawback(
+                &balances,
+                &definitions(),
+                "issuer_account_A",
+                "issuer_account_A",
+                Coin::new("denom1", 10)
+            ),
+            Err(BalanceChangeError::IssuerNotAllowed {
+                address: "issuer_account_A".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+    }
+}
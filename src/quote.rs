@@ -0,0 +1,430 @@
+use crate::allocation::allocate;
+use crate::error::BalanceChangeError;
+use crate::fees::{add_amount, balance_of, check_funds};
+use crate::types::{Balance, DenomDefinition, Feature, MultiSend};
+use crate::validation::{find_definition, merge_duplicates, validate, DuplicatePolicy};
+use std::collections::HashMap;
+
+// What one sender of a planned transfer is debited.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequiredInput {
+    pub address: String,
+    // The part of the outputs the sender pays for, the amount of its input.
+    pub amount: i128,
+    pub burn: i128,
+    pub commission: i128,
+    // amount + burn + commission, what the sender's balance must cover.
+    pub required: i128,
+}
+
+// The quote of a planned transfer of one denom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrossUp {
+    pub denom: String,
+    // One entry per sender, in the order of the planned inputs.
+    pub inputs: Vec<RequiredInput>,
+    pub total_burn: i128,
+    pub total_commission: i128,
+}
+
+impl GrossUp {
+    pub fn input(&self, address: &str) -> Option<&RequiredInput> {
+        self.inputs.iter().find(|input| input.address == address)
+    }
+}
+
+// Quotes what `sender` is debited so that `outputs` receive exactly their amounts of `definition.denom`.
+pub fn gross_up(
+    definition: &DenomDefinition,
+    sender: &str,
+    outputs: Vec<Balance>,
+) -> Result<GrossUp, BalanceChangeError> {
+    let amount = sum_of(std::slice::from_ref(definition), &outputs)?;
+    let planned = MultiSend {
+        inputs: vec![Balance::new(sender).with_coin(definition.denom.clone(), amount)],
+        outputs,
+    };
+    gross_up_multi(definition, &planned)
+}
+
+// Quotes what every co-sender of `planned` is debited. The inputs of `planned` are the parts of the outputs
+// that each sender pays for; burn and commission are added with the rounding of `calculate_balance_changes`.
+pub fn gross_up_multi(
+    definition: &DenomDefinition,
+    planned: &MultiSend,
+) -> Result<GrossUp, BalanceChangeError> {
+    let definitions = [definition.clone()];
+    validate(planned, &definitions)?;
+    let planned = MultiSend {
+        inputs: merge_duplicates(planned.inputs.clone(), DuplicatePolicy::Merge)?,
+        outputs: merge_duplicates(planned.outputs.clone(), DuplicatePolicy::Merge)?,
+    };
+    let inputs = sum_of(&definitions, &planned.inputs)?;
+    let outputs = sum_of(&definitions, &planned.outputs)?;
+    if inputs != outputs {
+        return Err(BalanceChangeError::InputOutputMismatch {
+            denom: definition.denom.clone(),
+            inputs,
+            outputs,
+        });
+    }
+
+    let allocation = allocate(definition, &planned)?;
+    let mut quote = GrossUp {
+        denom: definition.denom.clone(),
+        inputs: Vec::new(),
+        total_burn: allocation.total_burn_amount,
+        total_commission: allocation.total_commission_amount,
+    };
+    for (input, share) in planned.inputs.iter().zip(allocation.shares.iter()) {
+        // after merging every input has exactly one coin, of the quoted denom.
+        let amount = input.coins[0].amount;
+        let required = share
+            .burn
+            .checked_add(share.commission)
+            .and_then(|fees| fees.checked_add(amount))
+            .ok_or_else(|| BalanceChangeError::Overflow {
+                denom: definition.denom.clone(),
+            })?;
+        quote.inputs.push(RequiredInput {
+            address: input.address.clone(),
+            amount,
+            burn: share.burn,
+            commission: share.commission,
+            required,
+        });
+    }
+    Ok(quote)
+}
+
+// The largest amount of `definition.denom` that `sender` can send to `recipient` with its balance in
+// `original_balances`, so that the amount plus the rounded up burn and commission are covered. `fixed_outputs`
+// are paid in the same transaction; outputs to the issuer among them make the taxable base smaller. The frozen
+// part of the balance can not be sent, and a globally frozen denom only to the issuer.
+pub fn max_sendable(
+    original_balances: &[Balance],
+    definition: &DenomDefinition,
+    sender: &str,
+    recipient: &str,
+    fixed_outputs: Vec<Balance>,
+) -> Result<i128, BalanceChangeError> {
+    let denom = &definition.denom;
+    let mut available = balance_of(original_balances, sender, denom);
+    if sender != definition.issuer {
+        let to_holder = recipient != definition.issuer
+            || fixed_outputs
+                .iter()
+                .any(|output| output.address != definition.issuer);
+        if definition.is_globally_frozen() && to_holder {
+            return Err(BalanceChangeError::GloballyFrozen {
+                address: sender.to_string(),
+                denom: denom.clone(),
+            });
+        }
+        if definition.has_feature(Feature::Freezing) {
+            available = available
+                .saturating_sub(definition.frozen_amount(sender))
+                .max(0);
+        }
+    }
+    let required = |amount: i128| -> Result<i128, BalanceChangeError> {
+        let mut outputs = fixed_outputs.clone();
+        if amount > 0 {
+            outputs.push(Balance::new(recipient).with_coin(denom.clone(), amount));
+        }
+        if outputs.is_empty() {
+            return Ok(0);
+        }
+        Ok(gross_up(definition, sender, outputs)?.inputs[0].required)
+    };
+    let fixed_required = required(0)?;
+    check_funds(
+        original_balances,
+        std::slice::from_ref(definition),
+        sender,
+        denom,
+        fixed_required,
+    )?;
+    // an amount whose required input does not fit into an i128 is above any balance.
+    let is_covered = |amount: i128| -> Result<bool, BalanceChangeError> {
+        match required(amount) {
+            Ok(required) => Ok(required <= available),
+            Err(BalanceChangeError::Overflow { .. }) => Ok(false),
+            Err(error) => Err(error),
+        }
+    };
+    // the required amount grows with the amount sent, so the largest amount that fits is found by bisection
+    // between an amount that fits and one that does not.
+    let upper = available - fixed_required;
+    if is_covered(upper)? {
+        return Ok(upper);
+    }
+    let (mut fits, mut too_much) = (0i128, upper);
+    while too_much - fits > 1 {
+        let amount = fits + (too_much - fits) / 2;
+        if is_covered(amount)? {
+            fits = amount;
+        } else {
+            too_much = amount;
+        }
+    }
+    Ok(fits)
+}
+
+// The sum of the coins of `balances`, which must all be of the one denom of `definitions`.
+fn sum_of(
+    definitions: &[DenomDefinition],
+    balances: &[Balance],
+) -> Result<i128, BalanceChangeError> {
+    let mut sums: HashMap<String, i128> = HashMap::new();
+    for coin in balances.iter().flat_map(|balance| balance.coins.iter()) {
+        find_definition(definitions, &coin.denom)?;
+        add_amount(&mut sums, &coin.denom, coin.amount)?;
+    }
+    Ok(sums.into_values().next().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+    use crate::fees::calculate_balance_changes;
+    use crate::types::AccountAmount;
+
+    fn definition() -> DenomDefinition {
+        DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))
+    }
+
+    #[test]
+    fn single_sender_is_debited_amount_and_fees() {
+        let quote = gross_up(
+            &definition(),
+            "account1",
+            vec![Balance::new("account_recipient").with_coin("denom1", 1000)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            quote.inputs,
+            vec![RequiredInput {
+                address: "account1".to_string(),
+                amount: 1000,
+                burn: 80,
+                commission: 120,
+                required: 1200,
+            }]
+        );
+        assert_eq!((quote.total_burn, quote.total_commission), (80, 120));
+    }
+
+    #[test]
+    fn quote_matches_the_calculated_changes() {
+        // example 2 of the README: half of the outputs go to the issuer and are not taxed.
+        let planned = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 650))
+            .input(Balance::new("account2").with_coin("denom1", 350))
+            .output(Balance::new("account_recipient").with_coin("denom1", 500))
+            .output(Balance::new("issuer_account_A").with_coin("denom1", 500));
+
+        let quote = gross_up_multi(&definition(), &planned).unwrap();
+        let changes = calculate_balance_changes(
+            vec![
+                Balance::new("account1").with_coin("denom1", 715),
+                Balance::new("account2").with_coin("denom1", 385),
+            ],
+            vec![definition()],
+            planned,
+        )
+        .unwrap();
+
+        assert_eq!(quote.input("account1").unwrap().required, 715);
+        assert_eq!(quote.input("account2").unwrap().required, 385);
+        assert_eq!(
+            changes[0],
+            Balance::new("account1").with_coin("denom1", -715)
+        );
+        assert_eq!(
+            changes[1],
+            Balance::new("account2").with_coin("denom1", -385)
+        );
+    }
+
+    #[test]
+    fn issuer_sender_pays_no_fees() {
+        let quote = gross_up(
+            &definition(),
+            "issuer_account_A",
+            vec![Balance::new("account1").with_coin("denom1", 1000)],
+        )
+        .unwrap();
+
+        assert_eq!(quote.inputs[0].required, 1000);
+    }
+
+    #[test]
+    fn other_denoms_are_not_quoted() {
+        let result = gross_up(
+            &definition(),
+            "account1",
+            vec![Balance::new("account_recipient").with_coin("denom2", 1000)],
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::UnknownDenom {
+                denom: "denom2".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn max_to_a_single_recipient_leaves_room_for_fees() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+
+        let max = max_sendable(&balances, &definition(), "account1", "account2", Vec::new());
+
+        // 833 + roundup(66.64) + roundup(99.96) = 1000, 834 would need 1002.
+        assert_eq!(max, Ok(833));
+    }
+
+    #[test]
+    fn max_of_the_issuer_is_its_balance() {
+        let balances = vec![Balance::new("issuer_account_A").with_coin("denom1", 1000)];
+
+        let max = max_sendable(
+            &balances,
+            &definition(),
+            "issuer_account_A",
+            "account2",
+            Vec::new(),
+        );
+
+        assert_eq!(max, Ok(1000));
+    }
+
+    #[test]
+    fn outputs_to_the_issuer_shrink_the_taxable_base() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let fixed_outputs = vec![Balance::new("issuer_account_A").with_coin("denom1", 500)];
+
+        let max = max_sendable(
+            &balances,
+            &definition(),
+            "account1",
+            "account2",
+            fixed_outputs.clone(),
+        )
+        .unwrap();
+
+        // only the 416 to account2 are taxed: 500 + 416 + roundup(33.28) + roundup(49.92) = 1000.
+        assert_eq!(max, 416);
+        let quote = gross_up(
+            &definition(),
+            "account1",
+            vec![
+                fixed_outputs[0].clone(),
+                Balance::new("account2").with_coin("denom1", max + 1),
+            ],
+        )
+        .unwrap();
+        assert!(quote.inputs[0].required > 1000);
+    }
+
+    #[test]
+    fn fixed_outputs_above_the_balance_are_rejected() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", 100)];
+        let fixed_outputs = vec![Balance::new("account3").with_coin("denom1", 100)];
+
+        let max = max_sendable(
+            &balances,
+            &definition(),
+            "account1",
+            "account2",
+            fixed_outputs,
+        );
+
+        assert_eq!(
+            max,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 120,
+                available: 100,
+            })
+        );
+    }
+
+    #[test]
+    fn frozen_part_of_the_balance_is_not_sendable() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let mut definition = definition().with_feature(Feature::Freezing);
+        definition.frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 400,
+        });
+
+        let max = max_sendable(&balances, &definition, "account1", "account2", Vec::new());
+
+        // 500 + 40 + 60 = 600, what is left of 1000 after 400 are frozen.
+        assert_eq!(max, Ok(500));
+    }
+
+    #[test]
+    fn nothing_is_sendable_while_globally_frozen() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let mut definition = definition().with_feature(Feature::Freezing);
+        definition.globally_frozen = true;
+
+        let max = max_sendable(&balances, &definition, "account1", "account2", Vec::new());
+
+        assert_eq!(
+            max,
+            Err(BalanceChangeError::GloballyFrozen {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn max_to_the_issuer_during_a_global_freeze() {
+        let balances = vec![Balance::new("account1").with_coin("denom1", 1000)];
+        let mut definition = definition().with_feature(Feature::Freezing);
+        definition.globally_frozen = true;
+
+        let max = max_sendable(
+            &balances,
+            &definition,
+            "account1",
+            "issuer_account_A",
+            Vec::new(),
+        );
+
+        assert_eq!(max, Ok(1000));
+    }
+
+    #[test]
+    fn max_near_the_largest_balance() {
+        let available = i128::MAX / 6 * 5 + 10;
+        let balances = vec![Balance::new("account1").with_coin("denom1", available)];
+
+        // sending the whole balance would need more than i128::MAX.
+        let max =
+            max_sendable(&balances, &definition(), "account1", "account2", Vec::new()).unwrap();
+
+        let required = |amount| {
+            gross_up(
+                &definition(),
+                "account1",
+                vec![Balance::new("account2").with_coin("denom1", amount)],
+            )
+            .unwrap()
+            .inputs[0]
+                .required
+        };
+        assert!(required(max) <= available);
+        assert!(required(max + 1) > available);
+    }
+}
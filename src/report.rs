@@ -0,0 +1,297 @@
+use crate::allocation::Allocation;
+use crate::error::BalanceChangeError;
+use crate::types::{DenomDefinition, MultiSend};
+use std::collections::BTreeMap;
+
+// What makes up the balance change of one (address, denom). For example an input of 1000 with 8% burn
+// and 12% commission is `sent: 1000, burn_share: 80, commission_share: 120, net: -1200`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountReport {
+    pub address: String,
+    pub denom: String,
+    // The input of the account.
+    pub sent: i128,
+    // The outputs to the account.
+    pub received: i128,
+    // The part of the burn that the account pays on top of `sent`.
+    pub burn_share: i128,
+    // The part of the commission that the account pays on top of `sent`.
+    pub commission_share: i128,
+    // The commission the account receives as the issuer of the denom.
+    pub commission_received: i128,
+    // received + commission_received - sent - burn_share - commission_share, the balance change.
+    pub net: i128,
+}
+
+// The sums that burn and commission of one denom are calculated from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DenomReport {
+    pub denom: String,
+    pub non_issuer_input_sum: i128,
+    pub non_issuer_output_sum: i128,
+    // min(non_issuer_input_sum, non_issuer_output_sum), what the rates are applied to.
+    pub taxable_base: i128,
+    pub total_burn: i128,
+    pub total_commission: i128,
+}
+
+// A breakdown of the balance changes of a transaction, as the comments of the README examples give it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BalanceChangeReport {
+    // One entry per (address, denom) that is part of the transaction, sorted by address and denom.
+    pub accounts: Vec<AccountReport>,
+    // One entry per denom of the transaction, sorted by denom.
+    pub denoms: Vec<DenomReport>,
+}
+
+impl BalanceChangeReport {
+    pub fn account(&self, address: &str, denom: &str) -> Option<&AccountReport> {
+        self.accounts
+            .iter()
+            .find(|account| account.address == address && account.denom == denom)
+    }
+
+    pub fn denom(&self, denom: &str) -> Option<&DenomReport> {
+        self.denoms.iter().find(|report| report.denom == denom)
+    }
+}
+
+// Builds the report of `multi_send`, which must not list an (address, denom) twice, from the
+// allocations of its denoms.
+pub fn build_report(
+    multi_send: &MultiSend,
+    definitions: &[DenomDefinition],
+    allocations: &[Allocation],
+) -> Result<BalanceChangeReport, BalanceChangeError> {
+    let mut accounts: BTreeMap<(String, String), AccountReport> = BTreeMap::new();
+    for input in multi_send.inputs.iter() {
+        for coin in input.coins.iter() {
+            account_entry(&mut accounts, &input.address, &coin.denom).sent = coin.amount;
+        }
+    }
+    for output in multi_send.outputs.iter() {
+        for coin in output.coins.iter() {
+            account_entry(&mut accounts, &output.address, &coin.denom).received = coin.amount;
+        }
+    }
+    let mut denoms: Vec<DenomReport> = Vec::new();
+    for allocation in allocations {
+        for share in allocation.shares.iter() {
+            let account = account_entry(&mut accounts, &share.address, &allocation.denom);
+            account.burn_share = share.burn;
+            account.commission_share = share.commission;
+        }
+        if allocation.total_commission_amount > 0 {
+            let issuer = definitions
+                .iter()
+                .find(|definition| definition.denom == allocation.denom)
+                .map(|definition| definition.issuer.as_str())
+                .unwrap_or_default();
+            account_entry(&mut accounts, issuer, &allocation.denom).commission_received =
+                allocation.total_commission_amount;
+        }
+        denoms.push(DenomReport {
+            denom: allocation.denom.clone(),
+            non_issuer_input_sum: allocation.non_issuer_input_sum,
+            non_issuer_output_sum: allocation.non_issuer_output_sum,
+            taxable_base: allocation.taxable_base,
+            total_burn: allocation.total_burn_amount,
+            total_commission: allocation.total_commission_amount,
+        });
+    }
+    denoms.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    let mut report = BalanceChangeReport {
+        accounts: Vec::new(),
+        denoms,
+    };
+    for (_, mut account) in accounts {
+        let overflow = || BalanceChangeError::Overflow {
+            denom: account.denom.clone(),
+        };
+        account.net = account
+            .received
+            .checked_add(account.commission_received)
+            .and_then(|net| net.checked_sub(account.sent))
+            .and_then(|net| net.checked_sub(account.burn_share))
+            .and_then(|net| net.checked_sub(account.commission_share))
+            .ok_or_else(overflow)?;
+        report.accounts.push(account);
+    }
+    Ok(report)
+}
+
+fn account_entry<'a>(
+    accounts: &'a mut BTreeMap<(String, String), AccountReport>,
+    address: &str,
+    denom: &str,
+) -> &'a mut AccountReport {
+    accounts
+        .entry((address.to_string(), denom.to_string()))
+        .or_insert_with(|| AccountReport {
+            address: address.to_string(),
+            denom: denom.to_string(),
+            ..AccountReport::default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+    use crate::fees::{calculate_balance_changes_with, Options};
+    use crate::types::Balance;
+
+    #[test]
+    fn report_of_example_2() {
+        let original_balances = vec![
+            Balance::new("account1").with_coin("denom1", 1_000_000),
+            Balance::new("account2").with_coin("denom1", 1_000_000),
+        ];
+        let definitions = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 650))
+            .input(Balance::new("account2").with_coin("denom1", 350))
+            .output(Balance::new("account_recipient").with_coin("denom1", 500))
+            .output(Balance::new("issuer_account_A").with_coin("denom1", 500));
+
+        let changes = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::new().with_report(),
+        )
+        .unwrap();
+        let report = changes.report.unwrap();
+
+        assert_eq!(
+            report.account("account1", "denom1"),
+            Some(&AccountReport {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                sent: 650,
+                received: 0,
+                // 650 * 500 / 1000 * 0.08
+                burn_share: 26,
+                // 650 * 500 / 1000 * 0.12
+                commission_share: 39,
+                commission_received: 0,
+                net: -715,
+            })
+        );
+        assert_eq!(
+            report.account("issuer_account_A", "denom1"),
+            Some(&AccountReport {
+                address: "issuer_account_A".to_string(),
+                denom: "denom1".to_string(),
+                sent: 0,
+                received: 500,
+                burn_share: 0,
+                commission_share: 0,
+                commission_received: 60,
+                net: 560,
+            })
+        );
+        assert_eq!(
+            report.denoms,
+            vec![DenomReport {
+                denom: "denom1".to_string(),
+                non_issuer_input_sum: 1000,
+                non_issuer_output_sum: 500,
+                taxable_base: 500,
+                total_burn: 40,
+                total_commission: 60,
+            }]
+        );
+    }
+
+    #[test]
+    fn net_of_the_report_is_the_balance_change() {
+        let original_balances = vec![
+            Balance::new("account1")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+            Balance::new("account2")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom2", 10_000),
+        ];
+        let definitions = vec![
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(1, 1))
+                .with_commission_rate(Dec::with_prec(5, 2)),
+            DenomDefinition::new("denom2", "issuer_account_B")
+                .with_burn_rate(Dec::with_prec(15, 2))
+                .with_commission_rate(Dec::with_prec(1, 1)),
+        ];
+        // example 7, where account2 and the issuer of denom2 send and receive.
+        let multi_send = MultiSend::new()
+            .input(
+                Balance::new("account1")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 1000),
+            )
+            .input(
+                Balance::new("account2")
+                    .with_coin("denom1", 1000)
+                    .with_coin("denom2", 2000),
+            )
+            .output(
+                Balance::new("account_recipient")
+                    .with_coin("denom1", 2000)
+                    .with_coin("denom2", 2000),
+            )
+            .output(Balance::new("account_recipient_2").with_coin("denom2", 250))
+            .output(Balance::new("issuer_account_B").with_coin("denom2", 500))
+            .output(Balance::new("account2").with_coin("denom2", 250));
+
+        let changes = calculate_balance_changes_with(
+            original_balances,
+            definitions,
+            multi_send,
+            &Options::new().with_report(),
+        )
+        .unwrap();
+        let report = changes.report.unwrap();
+
+        let nets: Vec<(String, String, i128)> = report
+            .accounts
+            .iter()
+            .map(|account| (account.address.clone(), account.denom.clone(), account.net))
+            .collect();
+        let deltas: Vec<(String, String, i128)> = changes
+            .balances
+            .iter()
+            .flat_map(|balance| {
+                balance
+                    .coins
+                    .iter()
+                    .map(|coin| (balance.address.clone(), coin.denom.clone(), coin.amount))
+            })
+            .collect();
+        assert_eq!(nets, deltas);
+        assert_eq!(
+            report
+                .account("account2", "denom2")
+                .map(|a| (a.sent, a.received)),
+            Some((2000, 250))
+        );
+        assert_eq!(report.denom("denom2").unwrap().taxable_base, 2500);
+    }
+
+    #[test]
+    fn report_is_optional() {
+        let changes = calculate_balance_changes_with(
+            vec![Balance::new("account1").with_coin("denom1", 100)],
+            vec![DenomDefinition::new("denom1", "issuer_account_A")],
+            MultiSend::new()
+                .input(Balance::new("account1").with_coin("denom1", 100))
+                .output(Balance::new("account2").with_coin("denom1", 100)),
+            &Options::default(),
+        )
+        .unwrap();
+
+        assert_eq!(changes.report, None);
+    }
+}
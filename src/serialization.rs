@@ -0,0 +1,143 @@
+// JSON helpers for the `serde` feature. The layout follows the cosmos sdk: coin amounts (`sdk.Int`)
+// and rates (`sdk.Dec`) are strings, so large values survive JSON parsers that only know f64.
+
+// Serializes an `i128` coin amount as a decimal string, e.g. "1000000".
+pub mod amount_string {
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(amount: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(amount)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| de::Error::custom(format!("invalid amount {:?}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decimal::Dec;
+    use crate::types::{AccountAmount, Balance, Coin, DenomDefinition, Feature, MultiSend};
+
+    #[test]
+    fn coin_amount_is_a_string() {
+        let json = serde_json::to_string(&Coin::new("denom1", 1_000_000)).unwrap();
+
+        assert_eq!(json, r#"{"denom":"denom1","amount":"1000000"}"#);
+        assert_eq!(
+            serde_json::from_str::<Coin>(&json).unwrap(),
+            Coin::new("denom1", 1_000_000)
+        );
+    }
+
+    #[test]
+    fn amounts_beyond_f64_precision_round_trip() {
+        let amount = 1_000_000_000_000_000_000_000_000_000_001;
+        let json = serde_json::to_string(&Coin::new("denom1", amount)).unwrap();
+
+        assert_eq!(serde_json::from_str::<Coin>(&json).unwrap().amount, amount);
+    }
+
+    #[test]
+    fn numeric_and_malformed_amounts_are_rejected() {
+        assert!(serde_json::from_str::<Coin>(r#"{"denom":"denom1","amount":1000}"#).is_err());
+        assert!(serde_json::from_str::<Coin>(r#"{"denom":"denom1","amount":"1e3"}"#).is_err());
+    }
+
+    #[test]
+    fn balance_round_trips() {
+        let balance = Balance::new("account1")
+            .with_coin("denom1", 650)
+            .with_coin("denom2", 350);
+
+        let json = serde_json::to_string(&balance).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"address":"account1","coins":[{"denom":"denom1","amount":"650"},{"denom":"denom2","amount":"350"}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Balance>(&json).unwrap(), balance);
+    }
+
+    #[test]
+    fn denom_definition_rates_are_decimal_strings() {
+        let definition = DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2));
+
+        let json = serde_json::to_string(&definition).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"denom":"denom1","issuer":"issuer_account_A","burn_rate":"0.080000000000000000","commission_rate":"0.120000000000000000"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<DenomDefinition>(&json).unwrap(),
+            definition
+        );
+    }
+
+    #[test]
+    fn denom_definition_accepts_coreum_field_names() {
+        let json = r#"{
+            "denom": "denom1",
+            "issuer": "issuer_account_A",
+            "burn_rate": "0.08",
+            "send_commission_rate": "0.12"
+        }"#;
+
+        let definition: DenomDefinition = serde_json::from_str(json).unwrap();
+
+        assert_eq!(definition.commission_rate, Dec::with_prec(12, 2));
+    }
+
+    #[test]
+    fn multi_send_reads_a_cosmos_msg_multi_send() {
+        // as shown by chain explorers, including the message type.
+        let json = r#"{
+            "@type": "/cosmos.bank.v1beta1.MsgMultiSend",
+            "inputs": [
+                {"address": "account1", "coins": [{"denom": "denom1", "amount": "1000"}]}
+            ],
+            "outputs": [
+                {"address": "account_recipient", "coins": [{"denom": "denom1", "amount": "1000"}]}
+            ]
+        }"#;
+
+        let multi_send: MultiSend = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            multi_send,
+            MultiSend::new()
+                .input(Balance::new("account1").with_coin("denom1", 1000))
+                .output(Balance::new("account_recipient").with_coin("denom1", 1000))
+        );
+        let round_trip: MultiSend =
+            serde_json::from_str(&serde_json::to_string(&multi_send).unwrap()).unwrap();
+        assert_eq!(round_trip, multi_send);
+    }
+
+    #[test]
+    fn denom_definition_features_and_frozen_amounts() {
+        let mut definition =
+            DenomDefinition::new("denom1", "issuer_account_A").with_feature(Feature::Freezing);
+        definition.frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 100,
+        });
+
+        let json = serde_json::to_string(&definition).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"denom":"denom1","issuer":"issuer_account_A","burn_rate":"0.000000000000000000","commission_rate":"0.000000000000000000","features":["freezing"],"frozen":[{"address":"account1","amount":"100"}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<DenomDefinition>(&json).unwrap(),
+            definition
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// Instrumentation macros that forward to `tracing` with the `tracing` feature and expand to nothing
+// without it, so the library never writes to stdout or stderr on its own.
+//
+//     trace!(address = %input.address, denom = %coin.denom, required, "funds check");
+
+#[cfg(feature = "tracing")]
+macro_rules! trace {
+    ($($arg:tt)*) => { tracing::trace!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {};
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($arg:tt)*) => { tracing::debug!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {};
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::decimal::Dec;
+    use crate::fees::calculate_balance_changes;
+    use crate::types::{Balance, DenomDefinition, MultiSend};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    // Collects the names of spans and the messages of events.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{:?}", value);
+            }
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut lines = self.lines.lock().unwrap();
+            lines.push(format!("span {}", span.metadata().name()));
+            Id::from_u64(lines.len() as u64)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut message = Message(String::new());
+            event.record(&mut message);
+            self.lines.lock().unwrap().push(message.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn calculation_emits_spans_and_events() {
+        let recorder = Recorder::default();
+        let definitions =
+            vec![DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(8, 2))];
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1000))
+            .output(Balance::new("account_recipient").with_coin("denom1", 1000));
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            calculate_balance_changes(
+                vec![Balance::new("account1").with_coin("denom1", 10_000)],
+                definitions,
+                multi_send,
+            )
+            .unwrap();
+        });
+
+        assert_eq!(
+            *recorder.lines.lock().unwrap(),
+            vec![
+                "span multi_send",
+                "span denom",
+                "allocating fees",
+                "funds check",
+                "input share",
+            ]
+        );
+    }
+}
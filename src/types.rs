@@ -0,0 +1,273 @@
+use crate::decimal::Dec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// A user can submit a `MultiSend` transaction (similar to bank.MultiSend in cosmos sdk) to transfer multiple
+// coins (denoms) from multiple input addresses to multiple output addresses. A denom is the name or symbol
+// for a coin type, e.g USDT and USDC can be considered different denoms; in cosmos ecosystem they are called
+// denoms, in ethereum world they are called symbols.
+// The sum of input coins and output coins must match for every transaction.
+//
+// With the `serde` feature the JSON layout is the one of cosmos `bank.MsgMultiSend`, e.g.
+// {"inputs": [{"address": "account1", "coins": [{"denom": "denom1", "amount": "1000"}]}], "outputs": [..]}
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiSend {
+    // inputs contain the list of accounts that want to send coins from, and how many coins from each account we want to send.
+    pub inputs: Vec<Balance>,
+    // outputs contains the list of accounts that we want to deposit coins into, and how many coins to deposit into
+    // each account
+    pub outputs: Vec<Balance>,
+}
+
+impl MultiSend {
+    // An empty transaction, to be filled with `input` and `output`:
+    //     MultiSend::new()
+    //         .input(Balance::new("account1").with_coin("denom1", 1000))
+    //         .output(Balance::new("account2").with_coin("denom1", 1000))
+    pub fn new() -> MultiSend {
+        MultiSend::default()
+    }
+
+    pub fn input(mut self, input: Balance) -> MultiSend {
+        self.inputs.push(input);
+        self
+    }
+
+    pub fn output(mut self, output: Balance) -> MultiSend {
+        self.outputs.push(output);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Coin {
+    pub denom: String,
+    // encoded as a string in JSON, like `sdk.Int`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::amount_string"))]
+    pub amount: i128,
+}
+
+impl Coin {
+    pub fn new(denom: impl Into<String>, amount: i128) -> Coin {
+        Coin {
+            denom: denom.into(),
+            amount,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Balance {
+    pub address: String,
+    pub coins: Vec<Coin>,
+}
+
+impl Balance {
+    // A balance of `address` without any coins; add them with `with_coin`.
+    pub fn new(address: impl Into<String>) -> Balance {
+        Balance {
+            address: address.into(),
+            coins: Vec::new(),
+        }
+    }
+
+    pub fn with_coin(mut self, denom: impl Into<String>, amount: i128) -> Balance {
+        self.coins.push(Coin::new(denom, amount));
+        self
+    }
+}
+
+// A Denom has a definition (`CoinDefinition`) which contains different attributes related to the denom:
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DenomDefinition {
+    // the unique identifier for the token (e.g `core`, `eth`, `usdt`, etc.)
+    pub denom: String,
+    // The address that created the token
+    pub issuer: String,
+    // burn_rate is a number between 0 and 1. If it is above zero, in every transfer,
+    // some additional tokens will be burnt on top of the transferred value, from the senders address.
+    // The tokens to be burnt are calculated by multiplying the TransferAmount by burn rate, and
+    // rounding it up to an integer value. For example if an account sends 100 token and burn_rate is
+    // 0.2, then 120 (100 + 100 * 0.2) will be deducted from sender account and 100 will be deposited to the recipient
+    // account (i.e 20 tokens will be burnt)
+    pub burn_rate: Dec,
+    // commission_rate is exactly same as the burn_rate, but the calculated value will be transferred to the
+    // issuer's account address instead of being burnt.
+    // Coreum calls it `send_commission_rate`, which is accepted in JSON as well.
+    #[cfg_attr(feature = "serde", serde(alias = "send_commission_rate"))]
+    pub commission_rate: Dec,
+    // The features the issuer enabled when creating the token, like Coreum's asset module.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub features: Vec<Feature>,
+    // The part of the balance of an account that it can not send, set by the issuer with the freezing feature.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub frozen: Vec<AccountAmount>,
+    // The most that an account may hold, set by the issuer with the whitelisting feature. Accounts that are not
+    // listed can not receive the denom.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub whitelisted: Vec<AccountAmount>,
+    // Set by the issuer with the freezing feature to halt all transfers of the denom that do not come
+    // from or go to the issuer. Without the feature it has no effect.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub globally_frozen: bool,
+}
+
+// Optional abilities of the issuer of a denom. Coreum names them the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Feature {
+    // The issuer can freeze part of the balance of an account, or the whole denom.
+    Freezing,
+    // Accounts can only receive the denom up to a limit that the issuer sets per account.
+    Whitelisting,
+    // The issuer can create new tokens.
+    Minting,
+    // Holders can destroy their tokens. The issuer can burn its own without it, as in Coreum.
+    Burning,
+    // The issuer can take tokens back from any account.
+    Clawback,
+}
+
+// An amount of a denom that belongs to one account.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountAmount {
+    pub address: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::amount_string"))]
+    pub amount: i128,
+}
+
+impl DenomDefinition {
+    // A definition without burn and commission; set them with `with_burn_rate` and `with_commission_rate`.
+    pub fn new(denom: impl Into<String>, issuer: impl Into<String>) -> DenomDefinition {
+        DenomDefinition {
+            denom: denom.into(),
+            issuer: issuer.into(),
+            burn_rate: Dec::ZERO,
+            commission_rate: Dec::ZERO,
+            features: Vec::new(),
+            frozen: Vec::new(),
+            whitelisted: Vec::new(),
+            globally_frozen: false,
+        }
+    }
+
+    pub fn with_burn_rate(mut self, burn_rate: Dec) -> DenomDefinition {
+        self.burn_rate = burn_rate;
+        self
+    }
+
+    pub fn with_commission_rate(mut self, commission_rate: Dec) -> DenomDefinition {
+        self.commission_rate = commission_rate;
+        self
+    }
+
+    pub fn with_feature(mut self, feature: Feature) -> DenomDefinition {
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+        self
+    }
+
+    pub fn has_feature(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    // Whether the global freeze holds, which needs the freezing feature like the frozen amounts do.
+    pub fn is_globally_frozen(&self) -> bool {
+        self.globally_frozen && self.has_feature(Feature::Freezing)
+    }
+
+    // The frozen amount of `address`, zero if nothing is frozen.
+    pub fn frozen_amount(&self, address: &str) -> i128 {
+        self.frozen
+            .iter()
+            .find(|frozen| frozen.address == address)
+            .map_or(0, |frozen| frozen.amount)
+    }
+
+    // The whitelisted limit of `address`, zero if it is not whitelisted.
+    pub fn whitelisted_limit(&self, address: &str) -> i128 {
+        self.whitelisted
+            .iter()
+            .find(|whitelisted| whitelisted.address == address)
+            .map_or(0, |whitelisted| whitelisted.amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_match_struct_literals() {
+        let multi_send = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 1000))
+            .output(
+                Balance::new("account2")
+                    .with_coin("denom1", 600)
+                    .with_coin("denom1", 400),
+            );
+
+        assert_eq!(
+            multi_send,
+            MultiSend {
+                inputs: vec![Balance {
+                    address: "account1".to_string(),
+                    coins: vec![Coin {
+                        denom: "denom1".to_string(),
+                        amount: 1000,
+                    }],
+                }],
+                outputs: vec![Balance {
+                    address: "account2".to_string(),
+                    coins: vec![
+                        Coin {
+                            denom: "denom1".to_string(),
+                            amount: 600,
+                        },
+                        Coin {
+                            denom: "denom1".to_string(),
+                            amount: 400,
+                        },
+                    ],
+                }],
+            }
+        );
+        assert_eq!(
+            DenomDefinition::new("denom1", "issuer_account_A")
+                .with_burn_rate(Dec::with_prec(8, 2))
+                .with_commission_rate(Dec::with_prec(12, 2)),
+            DenomDefinition {
+                denom: "denom1".to_string(),
+                issuer: "issuer_account_A".to_string(),
+                burn_rate: "0.08".parse().unwrap(),
+                commission_rate: "0.12".parse().unwrap(),
+                features: Vec::new(),
+                frozen: Vec::new(),
+                whitelisted: Vec::new(),
+                globally_frozen: false,
+            }
+        );
+    }
+}
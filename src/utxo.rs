@@ -0,0 +1,598 @@
+use crate::error::BalanceChangeError;
+use crate::fees::{balance_of, calculate_balance_changes, check_whitelisting};
+use crate::types::{Balance, Coin, DenomDefinition, MultiSend};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Transactions are numbered in the order they are applied to a `UtxoSet`. The outputs created by
+// `UtxoSet::from_balances` belong to transaction 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TxId(pub u64);
+
+// Identifies the output at `index` of transaction `tx_id`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OutPoint {
+    pub tx_id: TxId,
+    pub index: u32,
+}
+
+// An unspent transaction output: `amount` of `denom` that can be spent by `owner`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Utxo {
+    pub owner: String,
+    pub denom: String,
+    pub amount: i128,
+}
+
+// A `MultiSend` funded by spending specific outputs. The spent outputs act as the original balances of
+// `calculate_balance_changes`, so burn and commission apply as usual. Whatever the spent outputs hold on
+// top of what their owner sends, burns and pays as commission comes back to the owner as a change output.
+#[derive(Clone, Debug)]
+pub struct UtxoTransaction {
+    pub spends: Vec<OutPoint>,
+    pub multi_send: MultiSend,
+}
+
+// The set of unspent outputs. Every output can be spent exactly once.
+#[derive(Debug, Default)]
+pub struct UtxoSet {
+    unspent: HashMap<OutPoint, Utxo>,
+    spent: HashSet<OutPoint>,
+    next_tx_id: u64,
+}
+
+impl UtxoSet {
+    // Creates one output per coin of `balances`, all belonging to transaction 0. The total of every denom
+    // has to fit into an i128; transactions never increase it, so the sums in `balances` can not overflow.
+    pub fn from_balances(balances: Vec<Balance>) -> Result<UtxoSet, BalanceChangeError> {
+        let mut set = UtxoSet {
+            next_tx_id: 1,
+            ..UtxoSet::default()
+        };
+        let mut index = 0;
+        let mut supply: HashMap<String, i128> = HashMap::new();
+        for balance in balances {
+            for coin in balance.coins {
+                if coin.amount < 0 {
+                    return Err(BalanceChangeError::InvalidAmount {
+                        address: balance.address,
+                        denom: coin.denom,
+                        amount: coin.amount,
+                    });
+                }
+                let total = supply.entry(coin.denom.clone()).or_insert(0);
+                *total =
+                    total
+                        .checked_add(coin.amount)
+                        .ok_or_else(|| BalanceChangeError::Overflow {
+                            denom: coin.denom.clone(),
+                        })?;
+                if coin.amount == 0 {
+                    continue;
+                }
+                let outpoint = OutPoint {
+                    tx_id: TxId(0),
+                    index,
+                };
+                set.unspent.insert(
+                    outpoint,
+                    Utxo {
+                        owner: balance.address.clone(),
+                        denom: coin.denom,
+                        amount: coin.amount,
+                    },
+                );
+                index += 1;
+            }
+        }
+        Ok(set)
+    }
+
+    pub fn get(&self, outpoint: &OutPoint) -> Option<&Utxo> {
+        self.unspent.get(outpoint)
+    }
+
+    // The unspent outputs of `owner`, ordered by outpoint.
+    pub fn unspent_outputs(&self, owner: &str) -> Vec<(OutPoint, Utxo)> {
+        let mut outputs: Vec<(OutPoint, Utxo)> = self
+            .unspent
+            .iter()
+            .filter(|(_, utxo)| utxo.owner == owner)
+            .map(|(outpoint, utxo)| (*outpoint, utxo.clone()))
+            .collect();
+        outputs.sort_by_key(|(outpoint, _)| *outpoint);
+        outputs
+    }
+
+    // The account view of the set: the sum of the unspent outputs per owner and denom, sorted by
+    // address and denom.
+    pub fn balances(&self) -> Vec<Balance> {
+        let mut amounts: BTreeMap<&str, BTreeMap<&str, i128>> = BTreeMap::new();
+        for utxo in self.unspent.values() {
+            *amounts
+                .entry(&utxo.owner)
+                .or_default()
+                .entry(&utxo.denom)
+                .or_insert(0) += utxo.amount;
+        }
+        amounts
+            .into_iter()
+            .map(|(address, coins)| Balance {
+                address: address.to_string(),
+                coins: coins
+                    .into_iter()
+                    .map(|(denom, amount)| Coin {
+                        denom: denom.to_string(),
+                        amount,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    // Spends the outputs of `tx` and creates its outputs: one per recipient, issuer commission and
+    // change, in the order of the balance changes. Returns the id of the transaction and the balance
+    // changes of its `MultiSend`. On error the set is not changed.
+    pub fn apply(
+        &mut self,
+        tx: UtxoTransaction,
+        definitions: &[DenomDefinition],
+    ) -> Result<(TxId, Vec<Balance>), BalanceChangeError> {
+        // (owner, denom) -> amount, in the order the spent outputs are listed.
+        let mut funds: Vec<(String, String, i128)> = Vec::new();
+        let mut spends: HashSet<OutPoint> = HashSet::new();
+        for outpoint in tx.spends.iter() {
+            if self.spent.contains(outpoint) || !spends.insert(*outpoint) {
+                return Err(BalanceChangeError::DoubleSpend {
+                    tx_id: outpoint.tx_id.0,
+                    index: outpoint.index,
+                });
+            }
+            let utxo = self
+                .unspent
+                .get(outpoint)
+                .ok_or(BalanceChangeError::UnknownOutPoint {
+                    tx_id: outpoint.tx_id.0,
+                    index: outpoint.index,
+                })?;
+            // only the owner of an output can spend it, and it does so with an input.
+            if !tx
+                .multi_send
+                .inputs
+                .iter()
+                .any(|input| input.address == utxo.owner)
+            {
+                return Err(BalanceChangeError::OutPointNotOwned {
+                    tx_id: outpoint.tx_id.0,
+                    index: outpoint.index,
+                    owner: utxo.owner.clone(),
+                });
+            }
+            add_to(&mut funds, &utxo.owner, &utxo.denom, utxo.amount)?;
+        }
+
+        let mut original_balances: Vec<Balance> = Vec::new();
+        for (owner, denom, amount) in funds.iter() {
+            let coin = Coin {
+                denom: denom.clone(),
+                amount: *amount,
+            };
+            match original_balances.iter_mut().find(|b| &b.address == owner) {
+                Some(balance) => balance.coins.push(coin),
+                None => original_balances.push(Balance {
+                    address: owner.clone(),
+                    coins: vec![coin],
+                }),
+            }
+        }
+        // The frozen part of a balance is checked against the spent outputs only, so the outputs that stay
+        // unspent hold the frozen funds first: an owner can spend min(spent, balance - frozen).
+        let holdings = self.balances();
+        let mut definitions = definitions.to_vec();
+        for definition in definitions.iter_mut() {
+            for frozen in definition.frozen.iter_mut() {
+                let unspent = balance_of(&holdings, &frozen.address, &definition.denom)
+                    - amount_of(&funds, &frozen.address, &definition.denom);
+                frozen.amount = frozen.amount.saturating_sub(unspent).max(0);
+            }
+        }
+        let recipients = tx.multi_send.outputs.clone();
+        let changes =
+            calculate_balance_changes(original_balances, definitions.clone(), tx.multi_send)?;
+        // the calculation only knows the spent funds, the whitelisted limits apply to the whole balance.
+        check_whitelisting(&holdings, &definitions, &recipients, &changes)?;
+
+        // The new outputs hold the spent funds plus the changes; funds without a change return as they are.
+        let mut outputs: Vec<(String, String, i128)> = Vec::new();
+        for balance in changes.iter() {
+            for coin in balance.coins.iter() {
+                if !outputs
+                    .iter()
+                    .any(|(owner, denom, _)| owner == &balance.address && denom == &coin.denom)
+                {
+                    let funded = amount_of(&funds, &balance.address, &coin.denom);
+                    outputs.push((balance.address.clone(), coin.denom.clone(), funded));
+                }
+                add_to(&mut outputs, &balance.address, &coin.denom, coin.amount)?;
+            }
+        }
+        for (owner, denom, amount) in funds.iter() {
+            if !outputs
+                .iter()
+                .any(|(address, coin_denom, _)| address == owner && coin_denom == denom)
+            {
+                outputs.push((owner.clone(), denom.clone(), *amount));
+            }
+        }
+        if let Some((owner, denom, amount)) = outputs.iter().find(|(_, _, amount)| *amount < 0) {
+            let available = amount_of(&funds, owner, denom);
+            return Err(BalanceChangeError::InsufficientFunds {
+                address: owner.clone(),
+                denom: denom.clone(),
+                required: available.saturating_sub(*amount),
+                available,
+            });
+        }
+
+        let tx_id = TxId(self.next_tx_id);
+        self.next_tx_id += 1;
+        for outpoint in spends {
+            self.unspent.remove(&outpoint);
+            self.spent.insert(outpoint);
+        }
+        let mut index = 0;
+        for (owner, denom, amount) in outputs {
+            if amount == 0 {
+                continue;
+            }
+            self.unspent.insert(
+                OutPoint { tx_id, index },
+                Utxo {
+                    owner,
+                    denom,
+                    amount,
+                },
+            );
+            index += 1;
+        }
+        Ok((tx_id, changes))
+    }
+}
+
+fn amount_of(amounts: &[(String, String, i128)], owner: &str, denom: &str) -> i128 {
+    amounts
+        .iter()
+        .filter(|(address, coin_denom, _)| address == owner && coin_denom == denom)
+        .map(|(_, _, amount)| *amount)
+        .sum()
+}
+
+fn add_to(
+    amounts: &mut Vec<(String, String, i128)>,
+    owner: &str,
+    denom: &str,
+    amount: i128,
+) -> Result<(), BalanceChangeError> {
+    match amounts
+        .iter_mut()
+        .find(|(address, coin_denom, _)| address == owner && coin_denom == denom)
+    {
+        Some((_, _, total)) => {
+            *total = total
+                .checked_add(amount)
+                .ok_or_else(|| BalanceChangeError::Overflow {
+                    denom: denom.to_string(),
+                })?
+        }
+        None => amounts.push((owner.to_string(), denom.to_string(), amount)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::Dec;
+    use crate::ledger::Ledger;
+    use crate::types::{AccountAmount, Feature};
+
+    fn definitions() -> Vec<DenomDefinition> {
+        vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_burn_rate(Dec::with_prec(8, 2))
+            .with_commission_rate(Dec::with_prec(12, 2))]
+    }
+
+    fn genesis() -> Vec<Balance> {
+        vec![
+            Balance::new("account1")
+                .with_coin("denom1", 10_000)
+                .with_coin("denom1", 100),
+            Balance::new("account2").with_coin("denom1", 5_000),
+        ]
+    }
+
+    fn outpoint(tx_id: u64, index: u32) -> OutPoint {
+        OutPoint {
+            tx_id: TxId(tx_id),
+            index,
+        }
+    }
+
+    fn send(spends: Vec<OutPoint>, from: &str, to: &str, amount: i128) -> UtxoTransaction {
+        UtxoTransaction {
+            spends,
+            multi_send: MultiSend::new()
+                .input(Balance::new(from).with_coin("denom1", amount))
+                .output(Balance::new(to).with_coin("denom1", amount)),
+        }
+    }
+
+    #[test]
+    fn genesis_creates_one_output_per_coin() {
+        let set = UtxoSet::from_balances(genesis()).unwrap();
+
+        assert_eq!(
+            set.unspent_outputs("account1"),
+            vec![
+                (
+                    outpoint(0, 0),
+                    Utxo {
+                        owner: "account1".to_string(),
+                        denom: "denom1".to_string(),
+                        amount: 10_000,
+                    }
+                ),
+                (
+                    outpoint(0, 1),
+                    Utxo {
+                        owner: "account1".to_string(),
+                        denom: "denom1".to_string(),
+                        amount: 100,
+                    }
+                ),
+            ]
+        );
+        assert_eq!(set.balances()[0].coins[0].amount, 10_100);
+    }
+
+    #[test]
+    fn transfer_creates_recipient_commission_and_change_outputs() {
+        let mut set = UtxoSet::from_balances(genesis()).unwrap();
+
+        let (tx_id, _) = set
+            .apply(
+                send(vec![outpoint(0, 0)], "account1", "account3", 1000),
+                &definitions(),
+            )
+            .unwrap();
+
+        assert_eq!(tx_id, TxId(1));
+        assert_eq!(set.get(&outpoint(0, 0)), None);
+        let created = |index| set.get(&outpoint(1, index)).cloned();
+        // outputs follow the canonical order of the changes.
+        // 10_000 spent, 1000 sent, 80 burnt and 120 commission
+        assert_eq!(
+            created(0),
+            Some(Utxo {
+                owner: "account1".to_string(),
+                denom: "denom1".to_string(),
+                amount: 8_800,
+            })
+        );
+        assert_eq!(
+            created(1),
+            Some(Utxo {
+                owner: "account3".to_string(),
+                denom: "denom1".to_string(),
+                amount: 1000,
+            })
+        );
+        assert_eq!(
+            created(2),
+            Some(Utxo {
+                owner: "issuer_account_A".to_string(),
+                denom: "denom1".to_string(),
+                amount: 120,
+            })
+        );
+        assert_eq!(created(3), None);
+        // the untouched output of account1 is still there.
+        assert_eq!(set.get(&outpoint(0, 1)).unwrap().amount, 100);
+    }
+
+    #[test]
+    fn account_view_matches_the_ledger() {
+        let mut set = UtxoSet::from_balances(genesis()).unwrap();
+        let mut ledger = Ledger::new(genesis(), definitions()).unwrap();
+
+        let tx = send(vec![outpoint(0, 2)], "account2", "account1", 2_000);
+        set.apply(tx.clone(), &definitions()).unwrap();
+        ledger.execute(tx.multi_send).unwrap();
+
+        assert_eq!(set.balances(), ledger.balances());
+    }
+
+    #[test]
+    fn spending_an_output_twice_is_rejected() {
+        let mut set = UtxoSet::from_balances(genesis()).unwrap();
+        set.apply(
+            send(vec![outpoint(0, 0)], "account1", "account3", 1000),
+            &definitions(),
+        )
+        .unwrap();
+        let balances = set.balances();
+
+        let again = set.apply(
+            send(vec![outpoint(0, 0)], "account1", "account3", 1000),
+            &definitions(),
+        );
+        let twice_in_one_tx = set.apply(
+            send(
+                vec![outpoint(1, 0), outpoint(1, 0)],
+                "account1",
+                "account3",
+                1000,
+            ),
+            &definitions(),
+        );
+
+        assert_eq!(
+            again,
+            Err(BalanceChangeError::DoubleSpend { tx_id: 0, index: 0 })
+        );
+        assert_eq!(
+            twice_in_one_tx,
+            Err(BalanceChangeError::DoubleSpend { tx_id: 1, index: 0 })
+        );
+        assert_eq!(set.balances(), balances);
+    }
+
+    #[test]
+    fn unknown_output_is_rejected() {
+        let mut set = UtxoSet::from_balances(genesis()).unwrap();
+
+        let result = set.apply(
+            send(vec![outpoint(7, 0)], "account1", "account3", 1000),
+            &definitions(),
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::UnknownOutPoint { tx_id: 7, index: 0 })
+        );
+    }
+
+    #[test]
+    fn outputs_of_other_owners_can_not_be_spent() {
+        let mut set = UtxoSet::from_balances(genesis()).unwrap();
+
+        // account1 sends, but spends the output of account2.
+        let result = set.apply(
+            send(
+                vec![outpoint(0, 0), outpoint(0, 2)],
+                "account1",
+                "account3",
+                1000,
+            ),
+            &definitions(),
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::OutPointNotOwned {
+                tx_id: 0,
+                index: 2,
+                owner: "account2".to_string(),
+            })
+        );
+        assert_eq!(set.get(&outpoint(0, 2)).unwrap().amount, 5_000);
+    }
+
+    #[test]
+    fn spent_outputs_must_cover_the_input_and_fees() {
+        let mut set = UtxoSet::from_balances(genesis()).unwrap();
+
+        // account1 holds 10_100 in total, but only spends its output of 100.
+        let result = set.apply(
+            send(vec![outpoint(0, 1)], "account1", "account3", 100),
+            &definitions(),
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::InsufficientFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 120,
+                available: 100,
+            })
+        );
+        assert_eq!(
+            set.balances(),
+            UtxoSet::from_balances(genesis()).unwrap().balances()
+        );
+    }
+
+    #[test]
+    fn total_supply_must_fit() {
+        let balances = vec![
+            Balance::new("account1").with_coin("denom1", i128::MAX),
+            Balance::new("account2").with_coin("denom1", 1),
+        ];
+
+        assert!(matches!(
+            UtxoSet::from_balances(balances),
+            Err(BalanceChangeError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn frozen_funds_are_held_by_the_unspent_outputs_first() {
+        let mut definition =
+            DenomDefinition::new("denom1", "issuer_account_A").with_feature(Feature::Freezing);
+        definition.frozen.push(AccountAmount {
+            address: "account1".to_string(),
+            amount: 50,
+        });
+        let definitions = vec![definition];
+        let balances = vec![Balance::new("account1")
+            .with_coin("denom1", 50)
+            .with_coin("denom1", 50)];
+        let mut set = UtxoSet::from_balances(balances).unwrap();
+
+        // the other output of 50 holds the frozen part.
+        set.apply(
+            send(vec![outpoint(0, 0)], "account1", "account3", 50),
+            &definitions,
+        )
+        .unwrap();
+        let result = set.apply(
+            send(vec![outpoint(0, 1)], "account1", "account3", 1),
+            &definitions,
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::FrozenFunds {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                required: 1,
+                spendable: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn whitelisted_limit_counts_the_unspent_outputs() {
+        let mut definition =
+            DenomDefinition::new("denom1", "issuer_account_A").with_feature(Feature::Whitelisting);
+        for address in ["account1", "account3"] {
+            definition.whitelisted.push(AccountAmount {
+                address: address.to_string(),
+                amount: 100,
+            });
+        }
+        let definitions = vec![definition];
+        let balances = vec![
+            Balance::new("account1").with_coin("denom1", 100),
+            Balance::new("account3").with_coin("denom1", 100),
+        ];
+        let mut set = UtxoSet::from_balances(balances).unwrap();
+
+        // account3 already holds its limit in an output that is not spent.
+        let result = set.apply(
+            send(vec![outpoint(0, 0)], "account1", "account3", 1),
+            &definitions,
+        );
+
+        assert_eq!(
+            result,
+            Err(BalanceChangeError::WhitelistedLimitExceeded {
+                address: "account3".to_string(),
+                denom: "denom1".to_string(),
+                balance: 101,
+                limit: 100,
+            })
+        );
+        assert_eq!(set.get(&outpoint(0, 0)).unwrap().amount, 100);
+    }
+}
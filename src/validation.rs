@@ -0,0 +1,400 @@
+use crate::decimal::Dec;
+use crate::error::BalanceChangeError;
+use crate::types::{Balance, Coin, DenomDefinition, MultiSend};
+
+// Longest address accepted, as `MaxAddrLen` in the cosmos sdk.
+const MAX_ADDRESS_LENGTH: usize = 255;
+
+// Checks the shape of `multi_send` before any fee is calculated: there are inputs and outputs, every one
+// of them has a valid address and lists coins, and every coin has a valid denom and a positive amount.
+// The definitions of the denoms that are used must have a valid issuer address and rates in [0, 1];
+// denoms without a definition are left to `calculate_balance_changes`.
+pub fn validate(
+    multi_send: &MultiSend,
+    definitions: &[DenomDefinition],
+) -> Result<(), BalanceChangeError> {
+    if multi_send.inputs.is_empty() {
+        return Err(BalanceChangeError::NoInputs);
+    }
+    if multi_send.outputs.is_empty() {
+        return Err(BalanceChangeError::NoOutputs);
+    }
+    for balance in multi_send.inputs.iter().chain(multi_send.outputs.iter()) {
+        check_address_format(&balance.address)?;
+        if balance.coins.is_empty() {
+            return Err(BalanceChangeError::NoCoins {
+                address: balance.address.clone(),
+            });
+        }
+        for coin in balance.coins.iter() {
+            check_denom(&coin.denom)?;
+            check_amount(&balance.address, coin)?;
+            if let Some(definition) = definitions.iter().find(|d| d.denom == coin.denom) {
+                check_address_format(&definition.issuer)?;
+                find_definition(definitions, &coin.denom)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Checks a denom against the cosmos sdk format `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`.
+pub fn check_denom(denom: &str) -> Result<(), BalanceChangeError> {
+    let mut chars = denom.chars();
+    let starts_with_letter = chars.next().is_some_and(|c| c.is_ascii_alphabetic());
+    let valid_rest = chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !starts_with_letter || !valid_rest || !(3..=128).contains(&denom.len()) {
+        return Err(BalanceChangeError::InvalidDenom {
+            denom: denom.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// Checks only the characters and length of an address: not empty, at most 255 bytes long and only ASCII
+// letters, digits, `_` and `-`. This accepts bech32 addresses like `core1...` as well as plain account
+// names, but it is not cosmos address validation: neither the bech32 prefix nor the checksum is checked.
+pub fn check_address_format(address: &str) -> Result<(), BalanceChangeError> {
+    let valid_chars = address
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if address.is_empty() || address.len() > MAX_ADDRESS_LENGTH || !valid_chars {
+        return Err(BalanceChangeError::InvalidAddress {
+            address: address.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// Checks that `coin` can be moved by `address`: only positive amounts can be transferred.
+pub fn check_amount(address: &str, coin: &Coin) -> Result<(), BalanceChangeError> {
+    if coin.amount <= 0 {
+        return Err(BalanceChangeError::InvalidAmount {
+            address: address.to_string(),
+            denom: coin.denom.clone(),
+            amount: coin.amount,
+        });
+    }
+    Ok(())
+}
+
+// How inputs or outputs that list the same (address, denom) more than once are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    // The amounts are added up, as if they were listed once.
+    #[default]
+    Merge,
+    // The transaction is rejected with `BalanceChangeError::DuplicateEntry`.
+    Reject,
+}
+
+// Aggregates `balances` (the inputs or the outputs of a transaction) per (address, denom), so that every
+// address is listed once with every denom once, in the order they first appear. Without this an address
+// listed twice in the inputs would have each entry checked against its full balance.
+pub fn merge_duplicates(
+    balances: Vec<Balance>,
+    policy: DuplicatePolicy,
+) -> Result<Vec<Balance>, BalanceChangeError> {
+    let mut merged: Vec<Balance> = Vec::new();
+    for balance in balances {
+        let index = match merged.iter().position(|b| b.address == balance.address) {
+            Some(index) => index,
+            None => {
+                merged.push(Balance::new(balance.address.clone()));
+                merged.len() - 1
+            }
+        };
+        for coin in balance.coins {
+            check_amount(&balance.address, &coin)?;
+            let coins = &mut merged[index].coins;
+            match coins.iter_mut().find(|c| c.denom == coin.denom) {
+                Some(_) if policy == DuplicatePolicy::Reject => {
+                    return Err(BalanceChangeError::DuplicateEntry {
+                        address: balance.address,
+                        denom: coin.denom,
+                    });
+                }
+                Some(existing) => {
+                    existing.amount = existing
+                        .amount
+                        .checked_add(coin.amount)
+                        .ok_or(BalanceChangeError::Overflow { denom: coin.denom })?;
+                }
+                None => coins.push(coin),
+            }
+        }
+    }
+    Ok(merged)
+}
+
+// Looks up the definition of `denom` and makes sure its rates are usable.
+pub fn find_definition<'a>(
+    definitions: &'a [DenomDefinition],
+    denom: &str,
+) -> Result<&'a DenomDefinition, BalanceChangeError> {
+    let definition = definitions
+        .iter()
+        .find(|d| d.denom == denom)
+        .ok_or_else(|| BalanceChangeError::UnknownDenom {
+            denom: denom.to_string(),
+        })?;
+    for rate in [definition.burn_rate, definition.commission_rate] {
+        if rate > Dec::ONE {
+            return Err(BalanceChangeError::InvalidRate {
+                denom: denom.to_string(),
+                rate,
+            });
+        }
+    }
+    Ok(definition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_and_zero_amounts_are_invalid() {
+        assert_eq!(check_amount("account1", &Coin::new("denom1", 1)), Ok(()));
+        assert_eq!(
+            check_amount("account1", &Coin::new("denom1", 0)),
+            Err(BalanceChangeError::InvalidAmount {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                amount: 0,
+            })
+        );
+        assert_eq!(
+            check_amount("account1", &Coin::new("denom1", -1)),
+            Err(BalanceChangeError::InvalidAmount {
+                address: "account1".to_string(),
+                denom: "denom1".to_string(),
+                amount: -1,
+            })
+        );
+    }
+
+    #[test]
+    fn definition_rates_must_not_exceed_one() {
+        let definitions = [
+            DenomDefinition::new("denom1", "issuer_account_A").with_burn_rate(Dec::ONE),
+            DenomDefinition::new("denom2", "issuer_account_B")
+                .with_commission_rate(Dec::with_prec(101, 2)),
+        ];
+
+        assert_eq!(find_definition(&definitions, "denom1"), Ok(&definitions[0]));
+        assert_eq!(
+            find_definition(&definitions, "denom2"),
+            Err(BalanceChangeError::InvalidRate {
+                denom: "denom2".to_string(),
+                rate: Dec::with_prec(101, 2),
+            })
+        );
+        assert_eq!(
+            find_definition(&definitions, "denom3"),
+            Err(BalanceChangeError::UnknownDenom {
+                denom: "denom3".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn duplicates_are_merged_in_order_of_appearance() {
+        let balances = vec![
+            Balance::new("account2").with_coin("denom2", 10),
+            Balance::new("account1").with_coin("denom1", 5),
+            Balance::new("account2")
+                .with_coin("denom1", 1)
+                .with_coin("denom2", 20),
+        ];
+
+        assert_eq!(
+            merge_duplicates(balances.clone(), DuplicatePolicy::Merge),
+            Ok(vec![
+                Balance::new("account2")
+                    .with_coin("denom2", 30)
+                    .with_coin("denom1", 1),
+                Balance::new("account1").with_coin("denom1", 5),
+            ])
+        );
+        assert_eq!(
+            merge_duplicates(balances, DuplicatePolicy::Reject),
+            Err(BalanceChangeError::DuplicateEntry {
+                address: "account2".to_string(),
+                denom: "denom2".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn merged_amounts_are_checked() {
+        let negative = vec![
+            Balance::new("account1").with_coin("denom1", 100),
+            Balance::new("account1").with_coin("denom1", -100),
+        ];
+        let overflowing = vec![
+            Balance::new("account1").with_coin("denom1", i128::MAX),
+            Balance::new("account1").with_coin("denom1", 1),
+        ];
+
+        assert!(matches!(
+            merge_duplicates(negative, DuplicatePolicy::Merge),
+            Err(BalanceChangeError::InvalidAmount { amount: -100, .. })
+        ));
+        assert!(matches!(
+            merge_duplicates(overflowing, DuplicatePolicy::Merge),
+            Err(BalanceChangeError::Overflow { .. })
+        ));
+    }
+
+    fn definitions() -> Vec<DenomDefinition> {
+        vec![DenomDefinition::new("denom1", "issuer_account_A")]
+    }
+
+    fn transfer(from: &str, to: &str, denom: &str, amount: i128) -> MultiSend {
+        MultiSend::new()
+            .input(Balance::new(from).with_coin(denom, amount))
+            .output(Balance::new(to).with_coin(denom, amount))
+    }
+
+    #[test]
+    fn valid_transaction_passes() {
+        assert_eq!(
+            validate(
+                &transfer("account1", "account2", "denom1", 100),
+                &definitions()
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn transaction_needs_inputs_outputs_and_coins() {
+        let no_inputs = MultiSend::new().output(Balance::new("account2").with_coin("denom1", 100));
+        let no_outputs = MultiSend::new().input(Balance::new("account1").with_coin("denom1", 100));
+        let no_coins = MultiSend::new()
+            .input(Balance::new("account1").with_coin("denom1", 100))
+            .output(Balance::new("account2"));
+
+        assert_eq!(
+            validate(&no_inputs, &definitions()),
+            Err(BalanceChangeError::NoInputs)
+        );
+        assert_eq!(
+            validate(&no_outputs, &definitions()),
+            Err(BalanceChangeError::NoOutputs)
+        );
+        assert_eq!(
+            validate(&no_coins, &definitions()),
+            Err(BalanceChangeError::NoCoins {
+                address: "account2".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn zero_and_negative_amounts_are_rejected() {
+        for amount in [0, -100] {
+            assert_eq!(
+                validate(
+                    &transfer("account1", "account2", "denom1", amount),
+                    &definitions()
+                ),
+                Err(BalanceChangeError::InvalidAmount {
+                    address: "account1".to_string(),
+                    denom: "denom1".to_string(),
+                    amount,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn denoms_follow_the_cosmos_format() {
+        for denom in [
+            "denom1",
+            "ucore",
+            "ibc/27394FB092D2ECCD",
+            "factory/core1abc/x_y-z.w:v",
+        ] {
+            assert_eq!(check_denom(denom), Ok(()), "{}", denom);
+        }
+        let too_long = format!("a{}", "b".repeat(128));
+        for denom in [
+            "",
+            "ab",
+            "1denom",
+            "/denom",
+            "den om",
+            "dénom",
+            too_long.as_str(),
+        ] {
+            assert_eq!(
+                check_denom(denom),
+                Err(BalanceChangeError::InvalidDenom {
+                    denom: denom.to_string(),
+                }),
+                "{}",
+                denom
+            );
+        }
+        assert_eq!(
+            validate(&transfer("account1", "account2", "", 100), &definitions()),
+            Err(BalanceChangeError::InvalidDenom {
+                denom: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn addresses_must_have_a_valid_format() {
+        for address in [
+            "account1",
+            "issuer_account_A",
+            "core1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v",
+        ] {
+            assert_eq!(check_address_format(address), Ok(()), "{}", address);
+        }
+        let too_long = "a".repeat(256);
+        for address in ["", "account 1", "account1\n", too_long.as_str()] {
+            assert_eq!(
+                check_address_format(address),
+                Err(BalanceChangeError::InvalidAddress {
+                    address: address.to_string(),
+                }),
+                "{:?}",
+                address
+            );
+        }
+        assert_eq!(
+            validate(&transfer("", "account2", "denom1", 100), &definitions()),
+            Err(BalanceChangeError::InvalidAddress {
+                address: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn definitions_of_used_denoms_are_checked() {
+        let bad_issuer = vec![DenomDefinition::new("denom1", "issuer account")];
+        let bad_rate = vec![DenomDefinition::new("denom1", "issuer_account_A")
+            .with_commission_rate(Dec::with_prec(2, 0))];
+        let unused = vec![
+            DenomDefinition::new("denom1", "issuer_account_A"),
+            DenomDefinition::new("denom2", ""),
+        ];
+        let multi_send = transfer("account1", "account2", "denom1", 100);
+
+        assert_eq!(
+            validate(&multi_send, &bad_issuer),
+            Err(BalanceChangeError::InvalidAddress {
+                address: "issuer account".to_string(),
+            })
+        );
+        assert!(matches!(
+            validate(&multi_send, &bad_rate),
+            Err(BalanceChangeError::InvalidRate { .. })
+        ));
+        assert_eq!(validate(&multi_send, &unused), Ok(()));
+    }
+}